@@ -0,0 +1,84 @@
+use crate::{error_hook, Err};
+use futures::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::panic::AssertUnwindSafe;
+
+mod sqs;
+mod stream;
+
+pub use sqs::{sqs, SqsBatch};
+pub use stream::{stream, StreamBatch, StreamEvent};
+
+/// The response of a function that [reports batch item
+/// failures](https://docs.aws.amazon.com/lambda/latest/dg/with-sqs.html#services-sqs-batchfailurereporting).
+///
+/// Only the records that failed are retried.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResponse {
+    /// The records that failed to be processed.
+    pub batch_item_failures: Vec<BatchItemFailure>,
+}
+
+/// A record that failed to be processed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchItemFailure {
+    /// The ID of the record.
+    pub item_identifier: String,
+}
+
+impl BatchResponse {
+    /// Returns a response reporting that the records identified by `ids` failed.
+    pub fn failures<I>(ids: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let failures = ids
+            .into_iter()
+            .map(|item_identifier| BatchItemFailure { item_identifier });
+        Self {
+            batch_item_failures: failures.collect(),
+        }
+    }
+}
+
+/// Runs `fut`, which processes the record identified by `id`, returning whether it succeeded.
+///
+/// Errors are transformed into reports by the hook registered with
+/// [`set_error_hook`](error_hook::set_error_hook), and panics are reported as `Panic`. Reports
+/// are logged with the ID of their record, as the response only includes the ID.
+async fn process<Fut, E>(id: &str, fut: Fut) -> bool
+where
+    Fut: Future<Output = Result<(), E>>,
+    E: Into<Err>,
+{
+    let report = match AssertUnwindSafe(fut).catch_unwind().await {
+        Ok(Ok(())) => return true,
+        Ok(Err(err)) => error_hook::report_error(err),
+        Err(payload) => error_hook::panic_report(&*payload),
+    };
+    let report = serde_json::to_string(&report).unwrap_or_default();
+    eprintln!("Failed to process record {id}: {report}");
+    false
+}
+
+/// Processes `records`, pairs of an ID and the future processing the record it identifies,
+/// one at a time and in order. Once one of them fails, the rest are not processed.
+///
+/// Returns the IDs of the record that failed and of those after it.
+async fn process_in_order<Fut, E>(records: Vec<(String, Fut)>) -> Vec<String>
+where
+    Fut: Future<Output = Result<(), E>>,
+    E: Into<Err>,
+{
+    let mut records = records.into_iter();
+    while let Some((id, fut)) = records.next() {
+        if !process(&id, fut).await {
+            return std::iter::once(id)
+                .chain(records.map(|(id, _)| id))
+                .collect();
+        }
+    }
+    vec![]
+}
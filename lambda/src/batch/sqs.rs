@@ -0,0 +1,196 @@
+use super::BatchResponse;
+use crate::{
+    events::sqs::{SqsEvent, SqsMessage},
+    Handler, LambdaCtx,
+};
+use futures::{future::BoxFuture, prelude::*, stream};
+use std::convert::Infallible;
+
+/// A [`Handler`] of SQS batches that runs another handler on each of their messages, and
+/// reports the messages it failed to process.
+///
+/// Returned by [`sqs`].
+#[derive(Debug, Clone)]
+pub struct SqsBatch<H> {
+    handler: H,
+    concurrency: usize,
+}
+
+/// Returns an [`SqsBatch`] that runs `handler` on each message of a batch, one at a time.
+///
+/// The function must be triggered with `ReportBatchItemFailures` enabled, otherwise the whole
+/// batch is deleted from the queue even if some of its messages failed.
+///
+/// # Example
+/// ```no_run
+/// #![feature(async_await)]
+///
+/// use lambda::{batch, events::sqs::SqsMessage, handler_fn, LambdaCtx};
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// #[runtime::main]
+/// async fn main() -> Result<(), Err> {
+///     let func = batch::sqs(handler_fn(func)).concurrency(10);
+///     lambda::run(func).await?;
+///     Ok(())
+/// }
+///
+/// async fn func(message: SqsMessage, _ctx: Option<LambdaCtx>) -> Result<(), Err> {
+///     let order: serde_json::Value = serde_json::from_str(&message.body)?;
+///     println!("received order {}", order["id"]);
+///     Ok(())
+/// }
+/// ```
+pub const fn sqs<H>(handler: H) -> SqsBatch<H>
+where
+    H: Handler<SqsMessage, ()>,
+{
+    SqsBatch {
+        handler,
+        concurrency: 1,
+    }
+}
+
+impl<H> SqsBatch<H> {
+    /// Processes up to `limit` messages of a batch at once.
+    ///
+    /// Messages from FIFO queues are always processed one at a time, in order. Once one of
+    /// them fails, the rest of the batch is reported as failed without being processed, so
+    /// that the messages are retried in order.
+    #[must_use]
+    pub fn concurrency(mut self, limit: usize) -> Self {
+        self.concurrency = limit.max(1);
+        self
+    }
+}
+
+impl<H> Handler<SqsEvent, BatchResponse> for SqsBatch<H>
+where
+    H: Handler<SqsMessage, ()>,
+    H::Fut: Send + 'static,
+    H::Err: Send,
+{
+    type Err = Infallible;
+    type Fut = BoxFuture<'static, Result<BatchResponse, Infallible>>;
+
+    fn call(&mut self, event: SqsEvent, ctx: Option<LambdaCtx>) -> Self::Fut {
+        // Only messages from FIFO queues belong to a message group.
+        let fifo = event
+            .records
+            .first()
+            .is_some_and(|message| message.attributes.contains_key("MessageGroupId"));
+        // The futures are created up front so that they do not borrow the handler, but are
+        // only polled once their turn comes.
+        let messages: Vec<_> = event
+            .records
+            .into_iter()
+            .map(|message| {
+                let id = message.message_id.clone();
+                (id, self.handler.call(message, ctx.clone()))
+            })
+            .collect();
+        let concurrency = self.concurrency;
+        async move {
+            if fifo {
+                let failures = super::process_in_order(messages).await;
+                return Ok(BatchResponse::failures(failures));
+            }
+            let results = stream::iter(messages)
+                .map(|(id, fut)| async move {
+                    let processed = super::process(&id, fut).await;
+                    (id, processed)
+                })
+                .buffered(concurrency);
+            let results: Vec<_> = results.collect().await;
+            let failures = results.into_iter().filter(|(_, processed)| !processed);
+            Ok(BatchResponse::failures(failures.map(|(id, _)| id)))
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sqs;
+    use crate::{
+        batch::BatchResponse, events::sqs::SqsMessage, handler_fn, testing, Err, LambdaCtx,
+    };
+    use std::time::Duration;
+
+    fn batch(ids: &[&str], fifo: bool) -> Vec<u8> {
+        let records: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                serde_json::json!({
+                    "messageId": id,
+                    "receiptHandle": "handle",
+                    "body": id,
+                    "md5OfBody": "",
+                    "eventSource": "aws:sqs",
+                    "attributes": if fifo {
+                        serde_json::json!({ "MessageGroupId": "group" })
+                    } else {
+                        serde_json::json!({})
+                    },
+                    "eventSourceARN": "arn:aws:sqs:us-east-1:123456789012:my-queue",
+                    "awsRegion": "us-east-1"
+                })
+            })
+            .collect();
+        serde_json::to_vec(&serde_json::json!({ "Records": records })).unwrap()
+    }
+
+    async fn fail_odd(message: SqsMessage, _ctx: Option<LambdaCtx>) -> Result<(), Err> {
+        let n: u64 = message.body.parse()?;
+        // Completes later messages first, to check that failures are reported in order.
+        runtime::time::Delay::new(Duration::from_millis(50 - n * 10)).await;
+        if n % 2 == 1 {
+            return Err(crate::err_fmt!("odd message {}", n).into());
+        }
+        Ok(())
+    }
+
+    fn response(ids: &[&str]) -> BatchResponse {
+        BatchResponse::failures(ids.iter().map(|id| (*id).to_string()))
+    }
+
+    async fn panic_on_second(message: SqsMessage, _ctx: Option<LambdaCtx>) -> Result<(), Err> {
+        if message.message_id.starts_with("2e14") {
+            panic!("cannot process {}", message.message_id);
+        }
+        Ok(())
+    }
+
+    #[runtime::test]
+    async fn report_failures() {
+        let event = include_str!("../../tests/events/sqs-event.json");
+        let ctx = testing::CtxBuilder::new().build();
+        let handler = sqs(handler_fn(panic_on_second));
+        let res = testing::invoke(handler, event.as_bytes(), ctx).await;
+        assert_eq!(
+            r#"{"batchItemFailures":[{"itemIdentifier":"2e1424d4-f796-459a-8184-9c92662be6da"}]}"#,
+            res.unwrap()
+        );
+    }
+
+    #[runtime::test]
+    async fn concurrent_messages() {
+        let event = batch(&["0", "1", "2", "3", "x"], false);
+        let ctx = testing::CtxBuilder::new().build();
+        let handler = sqs(handler_fn(fail_odd)).concurrency(5);
+        let res = testing::invoke(handler, &event, ctx).await.unwrap();
+        let res: BatchResponse = serde_json::from_slice(&res).unwrap();
+        assert_eq!(response(&["1", "3", "x"]), res);
+    }
+
+    #[runtime::test]
+    async fn fifo_messages() {
+        // "4" would succeed, but is not processed once "1" has failed.
+        let event = batch(&["0", "2", "1", "4"], true);
+        let ctx = testing::CtxBuilder::new().build();
+        let handler = sqs(handler_fn(fail_odd)).concurrency(5);
+        let res = testing::invoke(handler, &event, ctx).await.unwrap();
+        let res: BatchResponse = serde_json::from_slice(&res).unwrap();
+        assert_eq!(response(&["1", "4"]), res);
+    }
+}
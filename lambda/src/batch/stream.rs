@@ -0,0 +1,210 @@
+use super::BatchResponse;
+use crate::{
+    events::{
+        dynamodb::{DynamoDbEvent, DynamoDbRecord},
+        kinesis::{KinesisEvent, KinesisEventRecord},
+    },
+    Handler, LambdaCtx,
+};
+use futures::{future::BoxFuture, prelude::*};
+use serde::Deserialize;
+use std::{convert::Infallible, marker::PhantomData};
+
+/// A batch of records read from a stream, such as a [`KinesisEvent`] or a [`DynamoDbEvent`].
+pub trait StreamEvent: for<'de> Deserialize<'de> {
+    /// The type of the records in the batch.
+    type Record: for<'de> Deserialize<'de>;
+
+    /// Returns the records in the batch, in the order they were read from the stream.
+    fn into_records(self) -> Vec<Self::Record>;
+
+    /// Returns the sequence number of `record`, which identifies it in a
+    /// [`BatchItemFailure`](super::BatchItemFailure).
+    fn sequence_number(record: &Self::Record) -> &str;
+}
+
+impl StreamEvent for KinesisEvent {
+    type Record = KinesisEventRecord;
+
+    fn into_records(self) -> Vec<KinesisEventRecord> {
+        self.records
+    }
+
+    fn sequence_number(record: &KinesisEventRecord) -> &str {
+        &record.kinesis.sequence_number
+    }
+}
+
+impl StreamEvent for DynamoDbEvent {
+    type Record = DynamoDbRecord;
+
+    fn into_records(self) -> Vec<DynamoDbRecord> {
+        self.records
+    }
+
+    fn sequence_number(record: &DynamoDbRecord) -> &str {
+        &record.dynamodb.sequence_number
+    }
+}
+
+/// A [`Handler`] of stream batches that runs another handler on each of their records in
+/// order, and reports where to resume processing once a record fails.
+///
+/// Returned by [`stream`].
+#[derive(Debug, Clone)]
+pub struct StreamBatch<H, Event> {
+    handler: H,
+    event: PhantomData<fn() -> Event>,
+}
+
+/// Returns a [`StreamBatch`] that runs `handler` on each record of a batch, one at a time and
+/// in order.
+///
+/// Processing stops at the first record that fails, and its sequence number is reported as
+/// the checkpoint from which Lambda retries the stream, so records are never processed out
+/// of order. The function must be triggered with `ReportBatchItemFailures` enabled, otherwise
+/// the whole batch is considered processed.
+///
+/// # Example
+/// ```no_run
+/// #![feature(async_await)]
+///
+/// use lambda::{batch, events::kinesis::{KinesisEvent, KinesisEventRecord}, handler_fn, LambdaCtx};
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// #[runtime::main]
+/// async fn main() -> Result<(), Err> {
+///     let func = batch::stream::<_, KinesisEvent>(handler_fn(func));
+///     lambda::run(func).await?;
+///     Ok(())
+/// }
+///
+/// async fn func(record: KinesisEventRecord, _ctx: Option<LambdaCtx>) -> Result<(), Err> {
+///     let reading: serde_json::Value = serde_json::from_slice(record.kinesis.data.as_ref())?;
+///     println!("received reading {}", reading["value"]);
+///     Ok(())
+/// }
+/// ```
+pub const fn stream<H, Event>(handler: H) -> StreamBatch<H, Event>
+where
+    H: Handler<Event::Record, ()>,
+    Event: StreamEvent,
+{
+    StreamBatch {
+        handler,
+        event: PhantomData,
+    }
+}
+
+impl<H, Event> Handler<Event, BatchResponse> for StreamBatch<H, Event>
+where
+    H: Handler<Event::Record, ()>,
+    H::Fut: Send + 'static,
+    H::Err: Send,
+    Event: StreamEvent,
+{
+    type Err = Infallible;
+    type Fut = BoxFuture<'static, Result<BatchResponse, Infallible>>;
+
+    fn call(&mut self, event: Event, ctx: Option<LambdaCtx>) -> Self::Fut {
+        // As for FIFO queues, the futures are created up front but only polled in order.
+        let records: Vec<_> = event
+            .into_records()
+            .into_iter()
+            .map(|record| {
+                let sequence_number = Event::sequence_number(&record).to_string();
+                (sequence_number, self.handler.call(record, ctx.clone()))
+            })
+            .collect();
+        async move {
+            let failures = super::process_in_order(records).await;
+            // Lambda resumes from the first failure, so the records after it are not reported.
+            Ok(BatchResponse::failures(failures.into_iter().take(1)))
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stream;
+    use crate::{
+        batch::BatchResponse,
+        events::{
+            dynamodb::{DynamoDbEvent, DynamoDbRecord, OperationType},
+            kinesis::{KinesisEvent, KinesisEventRecord},
+        },
+        handler_fn, testing, Err, LambdaCtx,
+    };
+
+    fn batch(data: &[&str]) -> Vec<u8> {
+        let records: Vec<_> = data
+            .iter()
+            .enumerate()
+            .map(|(i, data)| {
+                serde_json::json!({
+                    "kinesis": {
+                        "kinesisSchemaVersion": "1.0",
+                        "partitionKey": "1",
+                        "sequenceNumber": (i + 1).to_string(),
+                        "data": base64::encode(data),
+                        "approximateArrivalTimestamp": 1_545_084_650.987
+                    },
+                    "eventSource": "aws:kinesis",
+                    "eventVersion": "1.0",
+                    "eventID": format!("shardId-000000000006:{}", i + 1),
+                    "eventName": "aws:kinesis:record",
+                    "invokeIdentityArn": "arn:aws:iam::123456789012:role/lambda-role",
+                    "awsRegion": "us-east-1",
+                    "eventSourceARN": "arn:aws:kinesis:us-east-1:123456789012:stream/lambda-stream"
+                })
+            })
+            .collect();
+        serde_json::to_vec(&serde_json::json!({ "Records": records })).unwrap()
+    }
+
+    async fn parse(record: KinesisEventRecord, _ctx: Option<LambdaCtx>) -> Result<(), Err> {
+        let data = String::from_utf8(record.kinesis.data.0)?;
+        if data == "panic" {
+            panic!("cannot process {}", record.event_id);
+        }
+        data.parse::<u64>()?;
+        Ok(())
+    }
+
+    async fn checkpoint(data: &[&str]) -> BatchResponse {
+        let ctx = testing::CtxBuilder::new().build();
+        let handler = stream::<_, KinesisEvent>(handler_fn(parse));
+        let res = testing::invoke(handler, &batch(data), ctx).await.unwrap();
+        serde_json::from_slice(&res).unwrap()
+    }
+
+    #[runtime::test]
+    async fn kinesis_checkpoint() {
+        let none = BatchResponse::default();
+        let second = BatchResponse::failures(vec!["2".to_string()]);
+        let third = BatchResponse::failures(vec!["3".to_string()]);
+        assert_eq!(none, checkpoint(&["1", "2", "3"]).await);
+        assert_eq!(second, checkpoint(&["1", "x", "3", "y"]).await);
+        assert_eq!(third, checkpoint(&["1", "2", "panic", "4"]).await);
+    }
+
+    async fn fail_removals(record: DynamoDbRecord, _ctx: Option<LambdaCtx>) -> Result<(), Err> {
+        if record.event_name == OperationType::Remove {
+            return Err(crate::err_fmt!("cannot remove {}", record.event_id).into());
+        }
+        Ok(())
+    }
+
+    #[runtime::test]
+    async fn dynamodb_checkpoint() {
+        let event = include_str!("../../tests/events/dynamodb-event.json");
+        let ctx = testing::CtxBuilder::new().build();
+        let handler = stream::<_, DynamoDbEvent>(handler_fn(fail_removals));
+        let res = testing::invoke(handler, event.as_bytes(), ctx).await;
+        assert_eq!(
+            r#"{"batchItemFailures":[{"itemIdentifier":"333"}]}"#,
+            res.unwrap()
+        );
+    }
+}
@@ -5,22 +5,32 @@ use futures::{
     prelude::*,
     task::{Context, Poll},
 };
-use http::{Method, Request, Response, Uri};
-use hyper::Body;
+use futures01::future::{ExecuteError, Executor, Future as Future01};
+use http::{HeaderMap, Method, Request, Response, Uri};
+use hyper::{client::HttpConnector, Body};
 use std::pin::Pin;
 
+/// An HTTP client for the [Lambda Runtime API](https://docs.aws.amazon.com/lambda/latest/dg/runtimes-api.html).
+///
+/// The paths of the requests passed to [`EventClient::call`] are resolved against the
+/// base `Uri` of the client.
 #[derive(Debug)]
-pub(crate) struct Client {
+pub struct Client {
     base: Uri,
-    client: hyper::Client<hyper::client::HttpConnector>,
+    client: hyper::Client<HttpConnector>,
 }
 
 impl Client {
-    pub(crate) fn new(uri: Uri) -> Self {
-        Self {
-            base: uri,
-            client: hyper::Client::new(),
-        }
+    /// Returns a client for the Runtime API at `uri`.
+    pub fn new(uri: Uri) -> Self {
+        let client = hyper::Client::builder().executor(Spawner).build_http();
+        Self::with_client(uri, client)
+    }
+
+    /// Returns a client for the Runtime API at `uri` that sends requests using `client`,
+    /// which may be configured with custom connection settings.
+    pub const fn with_client(uri: Uri, client: hyper::Client<HttpConnector>) -> Self {
+        Self { base: uri, client }
     }
 
     fn set_origin<B>(&self, req: Request<B>) -> Result<Request<B>, Err> {
@@ -52,10 +62,32 @@ impl Client {
     }
 }
 
+/// Spawns the background tasks of a hyper client onto the current [Runtime](runtime), as
+/// hyper otherwise expects to be running on a Tokio executor.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Spawner;
+
+impl<F> Executor<F> for Spawner
+where
+    F: Future01<Item = (), Error = ()> + Send + 'static,
+{
+    fn execute(&self, fut: F) -> Result<(), ExecuteError<F>> {
+        use futures::compat::Future01CompatExt;
+
+        runtime::spawn(fut.compat().map(|_| ()));
+        Ok(())
+    }
+}
+
 /// A trait modeling interactions with the [Lambda Runtime API](https://docs.aws.amazon.com/lambda/latest/dg/runtimes-api.html).
-pub(crate) trait EventClient<'a>: Send + Sync {
+///
+/// Implementing this trait allows the runtime to be driven by a transport other than
+/// [`Client`], such as an in-memory fake or a proxy that records each request.
+pub trait EventClient<'a>: Send + Sync {
     /// A future containing the next event from the Lambda Runtime API.
     type Fut: Future<Output = Result<Response<Bytes>, Err>> + Send + 'a;
+    /// Sends `req` to the Runtime API and returns its response. Requests only carry the
+    /// path and query of their `Uri`, such as `/runtime/invocation/next`.
     fn call(&self, req: Request<Bytes>) -> Self::Fut;
 }
 
@@ -107,6 +139,8 @@ where
 {
     current: Option<BoxFuture<'a, Result<Response<Bytes>, Err>>>,
     client: &'a T,
+    uri: Uri,
+    headers: HeaderMap,
 }
 
 impl<'a, T> EventStream<'a, T>
@@ -114,18 +148,27 @@ where
     T: EventClient<'a>,
 {
     pub(crate) fn new(inner: &'a T) -> Self {
+        let uri = Uri::from_static("/runtime/invocation/next");
+        Self::with_request(inner, uri, HeaderMap::new())
+    }
+
+    /// Returns a stream that polls `uri` for events, sending `headers` with each request.
+    pub(crate) fn with_request(inner: &'a T, uri: Uri, headers: HeaderMap) -> Self {
         Self {
             current: None,
             client: inner,
+            uri,
+            headers,
         }
     }
 
     pub(crate) fn next_event(&self) -> BoxFuture<'a, Result<Response<Bytes>, Err>> {
-        let req = Request::builder()
+        let mut req = Request::builder()
             .method(Method::GET)
-            .uri(Uri::from_static("/runtime/invocation/next"))
+            .uri(self.uri.clone())
             .body(Bytes::new())
             .unwrap();
+        *req.headers_mut() = self.headers.clone();
         Box::pin(self.client.call(req))
     }
 }
@@ -0,0 +1,157 @@
+use crate::Err;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+/// Decodes the events a handler receives, and encodes the outputs it returns.
+///
+/// The codec of a handler is chosen with [`Runtime::codec`](crate::Runtime::codec), and is
+/// [`Json`] by default. Errors returned while decoding an event or encoding an output fail
+/// the invocation, and are reported like those returned by the handler.
+pub trait Codec<Event, Output>: Send + Sync {
+    /// Decodes the payload of an invocation into the event passed to the handler.
+    ///
+    /// # Errors
+    /// Returns an error if the payload is not a valid `Event`.
+    fn decode(&self, event: &[u8]) -> Result<Event, Err>;
+    /// Encodes the output of the handler into the response payload of the invocation.
+    ///
+    /// # Errors
+    /// Returns an error if `output` cannot be encoded.
+    fn encode(&self, output: Output) -> Result<Bytes, Err>;
+}
+
+/// A [`Codec`] that deserializes events from JSON and serializes outputs to JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Json;
+
+impl<Event, Output> Codec<Event, Output> for Json
+where
+    Event: for<'de> Deserialize<'de>,
+    Output: Serialize,
+{
+    fn decode(&self, event: &[u8]) -> Result<Event, Err> {
+        Ok(serde_json::from_slice(event)?)
+    }
+
+    fn encode(&self, output: Output) -> Result<Bytes, Err> {
+        Ok(Bytes::from(serde_json::to_vec(&output)?))
+    }
+}
+
+/// A [`Codec`] that passes payloads to and from the handler as they are, without quoting
+/// or escaping them.
+///
+/// Events and outputs can be any [`RawPayload`], such as [`Bytes`] or a `String`.
+///
+/// # Example
+/// ```no_run
+/// #![feature(async_await)]
+///
+/// use lambda::{codec::Raw, handler_fn, LambdaCtx, Runtime};
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// #[runtime::main]
+/// async fn main() -> Result<(), Err> {
+///     Runtime::new().codec(Raw).run(handler_fn(func)).await?;
+///     Ok(())
+/// }
+///
+/// async fn func(event: String, _ctx: Option<LambdaCtx>) -> Result<String, Err> {
+///     Ok(event.lines().rev().collect::<Vec<_>>().join("\n"))
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Raw;
+
+impl<Event, Output> Codec<Event, Output> for Raw
+where
+    Event: RawPayload,
+    Output: RawPayload,
+{
+    fn decode(&self, event: &[u8]) -> Result<Event, Err> {
+        Event::from_bytes(event)
+    }
+
+    fn encode(&self, output: Output) -> Result<Bytes, Err> {
+        Ok(output.into_bytes())
+    }
+}
+
+/// A type that can be passed to and from a handler by the [`Raw`] codec.
+pub trait RawPayload: Sized {
+    /// Converts the payload of an invocation.
+    ///
+    /// # Errors
+    /// Returns an error if the payload is not a valid `Self`.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Err>;
+    /// Converts `self` into the payload of a response.
+    fn into_bytes(self) -> Bytes;
+}
+
+impl RawPayload for Bytes {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Err> {
+        Ok(Self::from(bytes))
+    }
+
+    fn into_bytes(self) -> Bytes {
+        self
+    }
+}
+
+impl RawPayload for Vec<u8> {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Err> {
+        Ok(bytes.to_vec())
+    }
+
+    fn into_bytes(self) -> Bytes {
+        Bytes::from(self)
+    }
+}
+
+/// Payloads that are not valid UTF-8 fail to convert.
+impl RawPayload for String {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Err> {
+        Ok(Self::from_utf8(bytes.to_vec())?)
+    }
+
+    fn into_bytes(self) -> Bytes {
+        Bytes::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Codec, Json, Raw};
+    use crate::{handler_fn, testing, Err, LambdaCtx, Runtime};
+    use bytes::Bytes;
+
+    async fn shout(event: String, _ctx: Option<LambdaCtx>) -> Result<String, Err> {
+        Ok(event.to_uppercase())
+    }
+
+    async fn reverse(event: Bytes, _ctx: Option<LambdaCtx>) -> Result<Vec<u8>, Err> {
+        Ok(event.iter().rev().cloned().collect())
+    }
+
+    #[test]
+    fn json() {
+        let event = Codec::<serde_json::Value, ()>::decode(&Json, br#"{"n": 1.5}"#);
+        assert_eq!(1.5, event.unwrap()["n"]);
+        let res = Codec::<(), _>::encode(&Json, "quoted").unwrap();
+        assert_eq!(&res[..], br#""quoted""#);
+    }
+
+    #[runtime::test]
+    async fn raw_payloads() {
+        let runtime = Runtime::new().codec(Raw);
+        let ctx = testing::CtxBuilder::new().build();
+        let res = testing::invoke_with(&runtime, handler_fn(shout), b"hello", ctx.clone()).await;
+        assert_eq!(&res.unwrap()[..], b"HELLO");
+
+        let res = testing::invoke_with(&runtime, handler_fn(reverse), b"\x00\xff", ctx.clone());
+        assert_eq!(&res.await.unwrap()[..], b"\xff\x00");
+
+        let report = testing::invoke_with(&runtime, handler_fn(shout), b"\xff", ctx).await;
+        assert!(report.unwrap_err().err.contains("utf-8"));
+    }
+}
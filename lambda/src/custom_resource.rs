@@ -0,0 +1,661 @@
+use crate::{
+    client::Spawner,
+    err_fmt,
+    error_hook::{self, ErrorReport},
+    Err, Handler, HandlerFn, LambdaCtx,
+};
+use futures::{
+    compat::Future01CompatExt,
+    future::{self, BoxFuture, Either},
+    prelude::*,
+};
+use http::{header::CONTENT_TYPE, Request};
+use hyper::Body;
+use hyper_tls::HttpsConnector;
+use pin_utils::pin_mut;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{fmt::Write, marker::PhantomData, panic::AssertUnwindSafe, time::Duration};
+
+/// How long before the invocation's deadline a handler that is still running is cancelled,
+/// leaving time to upload a `FAILED` response in its place.
+const DEADLINE_MARGIN: Duration = Duration::from_secs(1);
+
+/// The maximum length of the reason of a `FAILED` response, as the whole response must fit
+/// in 4 KiB.
+const MAX_REASON_LEN: usize = 1024;
+
+/// A request sent by CloudFormation to the function backing a [custom
+/// resource](https://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/crpg-ref-requests.html).
+///
+/// `Properties` is the type of the properties of the resource in the template. CloudFormation
+/// sends all scalar properties as strings, including numbers and booleans.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "RequestType")]
+pub enum CustomResourceRequest<Properties = Value> {
+    /// The resource is being created.
+    Create(CreateRequest<Properties>),
+    /// The properties of the resource changed.
+    Update(UpdateRequest<Properties>),
+    /// The resource is being deleted.
+    Delete(DeleteRequest<Properties>),
+}
+
+/// A request to create a custom resource.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct CreateRequest<Properties = Value> {
+    /// The ARN of the function, as set in the `ServiceToken` property of the resource.
+    pub service_token: String,
+    /// The presigned URL the response is uploaded to.
+    #[serde(rename = "ResponseURL")]
+    pub response_url: String,
+    /// The ARN of the stack the resource belongs to.
+    pub stack_id: String,
+    /// The ID of the request.
+    pub request_id: String,
+    /// The type of the resource, such as `Custom::Database`.
+    pub resource_type: String,
+    /// The name of the resource in the template.
+    pub logical_resource_id: String,
+    /// The properties of the resource.
+    pub resource_properties: Properties,
+}
+
+/// A request to update a custom resource whose properties changed.
+///
+/// Returning a different physical resource ID replaces the resource: CloudFormation then sends
+/// a [`DeleteRequest`] for the previous one.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateRequest<Properties = Value> {
+    /// The ARN of the function, as set in the `ServiceToken` property of the resource.
+    pub service_token: String,
+    /// The presigned URL the response is uploaded to.
+    #[serde(rename = "ResponseURL")]
+    pub response_url: String,
+    /// The ARN of the stack the resource belongs to.
+    pub stack_id: String,
+    /// The ID of the request.
+    pub request_id: String,
+    /// The type of the resource, such as `Custom::Database`.
+    pub resource_type: String,
+    /// The name of the resource in the template.
+    pub logical_resource_id: String,
+    /// The ID the function returned when the resource was created.
+    pub physical_resource_id: String,
+    /// The new properties of the resource.
+    pub resource_properties: Properties,
+    /// The properties of the resource before the update.
+    pub old_resource_properties: Properties,
+}
+
+/// A request to delete a custom resource.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteRequest<Properties = Value> {
+    /// The ARN of the function, as set in the `ServiceToken` property of the resource.
+    pub service_token: String,
+    /// The presigned URL the response is uploaded to.
+    #[serde(rename = "ResponseURL")]
+    pub response_url: String,
+    /// The ARN of the stack the resource belongs to.
+    pub stack_id: String,
+    /// The ID of the request.
+    pub request_id: String,
+    /// The type of the resource, such as `Custom::Database`.
+    pub resource_type: String,
+    /// The name of the resource in the template.
+    pub logical_resource_id: String,
+    /// The ID the function returned when the resource was created.
+    pub physical_resource_id: String,
+    /// The properties of the resource.
+    pub resource_properties: Properties,
+}
+
+/// The outcome of a request that a [`CustomResourceHandler`] processed successfully.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceOutput<Data = Value> {
+    /// The ID of the resource, which identifies it in later requests.
+    pub physical_resource_id: String,
+    /// The attributes of the resource, available to the template through `Fn::GetAtt`.
+    pub data: Option<Data>,
+    /// Whether to mask `data` when the resource is described.
+    pub no_echo: bool,
+}
+
+impl<Data> ResourceOutput<Data> {
+    /// Returns an output for the resource identified by `physical_resource_id`, without
+    /// attributes.
+    pub fn new<S: Into<String>>(physical_resource_id: S) -> Self {
+        Self {
+            physical_resource_id: physical_resource_id.into(),
+            data: None,
+            no_echo: false,
+        }
+    }
+
+    /// Sets the attributes of the resource.
+    #[must_use]
+    pub fn data(mut self, data: Data) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Masks the attributes of the resource when it is described.
+    #[must_use]
+    pub const fn no_echo(mut self) -> Self {
+        self.no_echo = true;
+        self
+    }
+}
+
+/// Whether a custom resource request succeeded.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ResponseStatus {
+    /// The request succeeded.
+    Success,
+    /// The request failed, and the stack operation is rolled back.
+    Failed,
+}
+
+/// The [response](https://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/crpg-ref-responses.html)
+/// uploaded to the `ResponseURL` of a custom resource request.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct CustomResourceResponse<Data = Value> {
+    /// Whether the request succeeded.
+    pub status: ResponseStatus,
+    /// Why the request failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The ID of the resource.
+    pub physical_resource_id: String,
+    /// The ARN of the stack the resource belongs to.
+    pub stack_id: String,
+    /// The ID of the request.
+    pub request_id: String,
+    /// The name of the resource in the template.
+    pub logical_resource_id: String,
+    /// Whether to mask `data` when the resource is described.
+    #[serde(default)]
+    pub no_echo: bool,
+    /// The attributes of the resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Data>,
+}
+
+/// The fields of a request that are needed to respond to it, even if the rest of the request
+/// is invalid.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct RequestHeader {
+    #[serde(rename = "ResponseURL")]
+    response_url: String,
+    stack_id: String,
+    request_id: String,
+    logical_resource_id: String,
+    physical_resource_id: Option<String>,
+}
+
+/// A handler of the requests CloudFormation sends to manage a custom resource.
+///
+/// Handlers are run through an [`Adapter`], which uploads the response to CloudFormation
+/// whatever the outcome of the request. `CustomResourceHandler` is implemented by
+/// [`HandlerFn`] for closures taking a [`CustomResourceRequest`].
+pub trait CustomResourceHandler<Properties, Data>
+where
+    Properties: for<'de> Deserialize<'de>,
+    Data: Serialize,
+{
+    /// Errors returned by this handler.
+    type Err: Into<Err>;
+    /// The future outcome of a request.
+    type Fut: Future<Output = Result<ResourceOutput<Data>, Self::Err>>;
+
+    /// Creates, updates or deletes the resource described by `req`.
+    fn call(&mut self, req: CustomResourceRequest<Properties>, ctx: Option<LambdaCtx>)
+        -> Self::Fut;
+}
+
+impl<Function, Properties, Data, Error, Fut> CustomResourceHandler<Properties, Data>
+    for HandlerFn<Function>
+where
+    Function: Fn(CustomResourceRequest<Properties>, Option<LambdaCtx>) -> Fut,
+    Properties: for<'de> Deserialize<'de>,
+    Data: Serialize,
+    Error: Into<Err>,
+    Fut: Future<Output = Result<ResourceOutput<Data>, Error>> + Send,
+{
+    type Err = Error;
+    type Fut = Fut;
+
+    fn call(&mut self, req: CustomResourceRequest<Properties>, ctx: Option<LambdaCtx>) -> Fut {
+        (self.f)(req, ctx)
+    }
+}
+
+/// A [`Handler`] that runs a [`CustomResourceHandler`] and uploads its outcome to the
+/// `ResponseURL` of the request.
+///
+/// Returned by [`adapter`].
+#[derive(Debug, Clone)]
+pub struct Adapter<H, Properties, Data> {
+    handler: H,
+    types: PhantomData<fn() -> (Properties, Data)>,
+}
+
+/// Returns an [`Adapter`] that runs `handler`.
+///
+/// A response is uploaded for every request, so that the stack never waits for the
+/// function until CloudFormation times out. Errors returned by `handler` are transformed into
+/// the reason of a `FAILED` response using the hook registered with
+/// [`set_error_hook`](crate::error_hook::set_error_hook), and so are panics, requests whose
+/// properties cannot be deserialized, and handlers still running one second before the
+/// invocation's deadline. The full error report is logged.
+///
+/// # Example
+/// ```no_run
+/// #![feature(async_await)]
+///
+/// use lambda::{
+///     custom_resource::{self, CustomResourceRequest, ResourceOutput},
+///     handler_fn, LambdaCtx,
+/// };
+/// use serde::Deserialize;
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// #[derive(Deserialize)]
+/// #[serde(rename_all = "PascalCase")]
+/// struct Properties {
+///     bucket_name: String,
+/// }
+///
+/// #[runtime::main]
+/// async fn main() -> Result<(), Err> {
+///     lambda::run(custom_resource::adapter(handler_fn(func))).await?;
+///     Ok(())
+/// }
+///
+/// async fn func(
+///     req: CustomResourceRequest<Properties>,
+///     _ctx: Option<LambdaCtx>,
+/// ) -> Result<ResourceOutput<serde_json::Value>, Err> {
+///     match req {
+///         CustomResourceRequest::Create(req) => {
+///             Ok(ResourceOutput::new(req.resource_properties.bucket_name))
+///         }
+///         CustomResourceRequest::Update(req) => Ok(ResourceOutput::new(req.physical_resource_id)),
+///         CustomResourceRequest::Delete(req) => Ok(ResourceOutput::new(req.physical_resource_id)),
+///     }
+/// }
+/// ```
+pub const fn adapter<H, Properties, Data>(handler: H) -> Adapter<H, Properties, Data>
+where
+    H: CustomResourceHandler<Properties, Data>,
+    Properties: for<'de> Deserialize<'de>,
+    Data: Serialize,
+{
+    Adapter {
+        handler,
+        types: PhantomData,
+    }
+}
+
+impl<H, Properties, Data> Handler<Value, ()> for Adapter<H, Properties, Data>
+where
+    H: CustomResourceHandler<Properties, Data>,
+    H::Fut: Send + 'static,
+    H::Err: Send,
+    Properties: for<'de> Deserialize<'de>,
+    Data: Serialize + Send + 'static,
+{
+    type Err = Err;
+    type Fut = BoxFuture<'static, Result<(), Err>>;
+
+    fn call(&mut self, event: Value, ctx: Option<LambdaCtx>) -> Self::Fut {
+        // Without these fields, there is nowhere to respond to, so the invocation fails instead.
+        let header = match RequestHeader::deserialize(&event) {
+            Ok(header) => header,
+            Err(err) => return future::ready(Err(err.into())).boxed(),
+        };
+        let ctx = ctx.unwrap_or_default();
+        let fut =
+            serde_json::from_value(event).map(|req| self.handler.call(req, Some(ctx.clone())));
+        async move {
+            let outcome = match fut {
+                Ok(fut) => run(fut, &ctx).await,
+                Err(err) => Err(error_hook::report_error(err)),
+            };
+            let response = serde_json::to_vec(&respond(&header, outcome, &ctx))?;
+            upload(&header.response_url, response).await
+        }
+        .boxed()
+    }
+}
+
+/// Runs `fut` until it completes, panics, or the invocation is about to time out.
+///
+/// Without a deadline, such as when the adapter is called without a context, `fut` runs
+/// until it completes or panics.
+async fn run<Fut, Data, E>(fut: Fut, ctx: &LambdaCtx) -> Result<ResourceOutput<Data>, ErrorReport>
+where
+    Fut: Future<Output = Result<ResourceOutput<Data>, E>>,
+    E: Into<Err>,
+{
+    let fut = AssertUnwindSafe(fut).catch_unwind();
+    let res = if ctx.deadline == 0 {
+        Some(fut.await)
+    } else {
+        let timeout = ctx.deadline_reached(DEADLINE_MARGIN);
+        pin_mut!(fut);
+        match future::select(fut, timeout).await {
+            Either::Left((res, _)) => Some(res),
+            Either::Right(_) => None,
+        }
+    };
+    match res {
+        Some(Ok(Ok(output))) => Ok(output),
+        Some(Ok(Err(err))) => Err(error_hook::report_error(err)),
+        Some(Err(payload)) => Err(error_hook::panic_report(&*payload)),
+        None => Err(error_hook::timeout_report(ctx.deadline)),
+    }
+}
+
+/// Builds the response to the request described by `header`.
+fn respond<Data>(
+    header: &RequestHeader,
+    outcome: Result<ResourceOutput<Data>, ErrorReport>,
+    ctx: &LambdaCtx,
+) -> CustomResourceResponse<Data> {
+    let (status, reason, physical_resource_id, no_echo, data) = match outcome {
+        Ok(output) => (
+            ResponseStatus::Success,
+            None,
+            output.physical_resource_id,
+            output.no_echo,
+            output.data,
+        ),
+        Err(report) => {
+            eprintln!(
+                "Custom resource request {} failed: {}",
+                header.request_id,
+                serde_json::to_string(&report).unwrap_or_default()
+            );
+            // A resource that failed to be created has no ID yet, but the response needs one.
+            let physical_resource_id = header.physical_resource_id.clone().unwrap_or_else(|| {
+                match ctx.env_config.log_stream.as_str() {
+                    "" => header.request_id.clone(),
+                    log_stream => log_stream.to_string(),
+                }
+            });
+            let reason = reason(&report, &ctx.env_config.log_stream);
+            (
+                ResponseStatus::Failed,
+                Some(reason),
+                physical_resource_id,
+                false,
+                None,
+            )
+        }
+    };
+    CustomResourceResponse {
+        status,
+        reason,
+        physical_resource_id,
+        stack_id: header.stack_id.clone(),
+        request_id: header.request_id.clone(),
+        logical_resource_id: header.logical_resource_id.clone(),
+        no_echo,
+        data,
+    }
+}
+
+/// Describes why a request failed, pointing at the logs for the details.
+fn reason(report: &ErrorReport, log_stream: &str) -> String {
+    let mut reason = format!("{}: {}", report.name, report.err);
+    if reason.len() > MAX_REASON_LEN {
+        let end = (0..=MAX_REASON_LEN)
+            .rev()
+            .find(|i| reason.is_char_boundary(*i))
+            .unwrap_or_default();
+        reason.truncate(end);
+    }
+    if !log_stream.is_empty() {
+        let _ = write!(reason, " (see CloudWatch log stream {log_stream})");
+    }
+    reason
+}
+
+/// Uploads the serialized `response` to the presigned `url`.
+async fn upload(url: &str, response: Vec<u8>) -> Result<(), Err> {
+    // The URL is signed without a content type, so none must be sent.
+    let req = Request::put(url)
+        .header(CONTENT_TYPE, "")
+        .body(Body::from(response))?;
+    let connector = HttpsConnector::new(1)?;
+    let client = hyper::Client::builder()
+        .executor(Spawner)
+        .build::<_, Body>(connector);
+    let res = client.request(req).compat().await?;
+    if !res.status().is_success() {
+        return Err(err_fmt!("Failed to upload the response: {}", res.status()).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        adapter, CustomResourceRequest, CustomResourceResponse, ResourceOutput, ResponseStatus,
+    };
+    use crate::{handler_fn, testing, Err, Handler, LambdaCtx};
+    use bytes::Bytes;
+    use futures01::{sync::oneshot, Future, Stream};
+    use http::{Method, Request, Response, StatusCode};
+    use hyper::{service::service_fn, Body, Server};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use std::{
+        collections::HashMap,
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    type Objects = Arc<Mutex<HashMap<String, Bytes>>>;
+
+    /// A local stand-in for the S3 bucket behind the presigned `ResponseURL` of a request,
+    /// which records the objects uploaded to it and stops when it is dropped.
+    struct ResponseBucket {
+        addr: SocketAddr,
+        objects: Objects,
+        shutdown: Option<oneshot::Sender<()>>,
+    }
+
+    impl ResponseBucket {
+        fn start() -> Result<Self, Err> {
+            let objects = Objects::default();
+            let server = Server::try_bind(&([127, 0, 0, 1], 0).into())?;
+            let shared = Arc::clone(&objects);
+            let server = server.serve(move || {
+                let objects = Arc::clone(&shared);
+                service_fn(move |req| put_object(&objects, req))
+            });
+            let addr = server.local_addr();
+
+            let (shutdown, signal) = oneshot::channel();
+            let server = server
+                .with_graceful_shutdown(signal)
+                .map_err(|err| eprintln!("Response bucket failed: {err}"));
+            thread::spawn(move || hyper::rt::run(server));
+
+            Ok(Self {
+                addr,
+                objects,
+                shutdown: Some(shutdown),
+            })
+        }
+
+        /// Returns a URL that uploads an object named `key`. Like a presigned URL, it carries a
+        /// query string, which the bucket ignores.
+        fn url(&self, key: &str) -> String {
+            format!("http://{}/{key}?X-Amz-Signature=emulated", self.addr)
+        }
+
+        fn object(&self, key: &str) -> Option<Bytes> {
+            self.objects.lock().unwrap().get(key).cloned()
+        }
+    }
+
+    impl Drop for ResponseBucket {
+        fn drop(&mut self) {
+            if let Some(shutdown) = self.shutdown.take() {
+                let _ = shutdown.send(());
+            }
+        }
+    }
+
+    fn put_object(
+        objects: &Objects,
+        req: Request<Body>,
+    ) -> impl Future<Item = Response<Body>, Error = hyper::Error> {
+        let objects = Arc::clone(objects);
+        let (parts, body) = req.into_parts();
+        body.concat2().map(move |body| {
+            let status = if parts.method == Method::PUT {
+                let key = parts.uri.path().trim_start_matches('/');
+                let mut objects = objects.lock().unwrap();
+                objects.insert(String::from(key), body.into_bytes());
+                StatusCode::OK
+            } else {
+                StatusCode::METHOD_NOT_ALLOWED
+            };
+            let mut res = Response::new(Body::empty());
+            *res.status_mut() = status;
+            res
+        })
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "PascalCase")]
+    struct Properties {
+        name: String,
+        #[serde(default)]
+        fail: Option<String>,
+    }
+
+    async fn func(
+        req: CustomResourceRequest<Properties>,
+        _ctx: Option<LambdaCtx>,
+    ) -> Result<ResourceOutput<Value>, Err> {
+        match req {
+            CustomResourceRequest::Create(req) => {
+                let props = req.resource_properties;
+                match props.fail.as_ref().map(String::as_str) {
+                    Some("error") => Err(crate::err_fmt!("cannot create {}", props.name).into()),
+                    Some("panic") => panic!("cannot create {}", props.name),
+                    Some("sleep") => {
+                        runtime::time::Delay::new(Duration::from_millis(50)).await;
+                        Ok(ResourceOutput::new(format!("id-{}", props.name)))
+                    }
+                    _ => {
+                        let output = ResourceOutput::new(format!("id-{}", props.name));
+                        Ok(output.data(json!({ "Arn": "arn:aws:example" })))
+                    }
+                }
+            }
+            CustomResourceRequest::Update(req) => {
+                assert_eq!("old", req.old_resource_properties.name);
+                Ok(ResourceOutput::new(req.physical_resource_id).no_echo())
+            }
+            CustomResourceRequest::Delete(req) => Ok(ResourceOutput::new(req.physical_resource_id)),
+        }
+    }
+
+    fn request(bucket: &ResponseBucket, kind: &str, props: Value) -> Vec<u8> {
+        let mut req = json!({
+            "RequestType": kind,
+            "ServiceToken": "arn:aws:lambda:us-east-1:123456789012:function:resource",
+            "ResponseURL": bucket.url("response"),
+            "StackId": "arn:aws:cloudformation:us-east-1:123456789012:stack/stack/guid",
+            "RequestId": "unique-id",
+            "ResourceType": "Custom::Test",
+            "LogicalResourceId": "Resource",
+            "ResourceProperties": props
+        });
+        if kind != "Create" {
+            req["PhysicalResourceId"] = json!("id-existing");
+            req["OldResourceProperties"] = json!({ "Name": "old" });
+        }
+        serde_json::to_vec(&req).unwrap()
+    }
+
+    async fn respond(kind: &str, props: Value) -> CustomResourceResponse {
+        let bucket = ResponseBucket::start().unwrap();
+        let req = request(&bucket, kind, props);
+        let ctx = testing::CtxBuilder::new().build();
+        testing::invoke(adapter(handler_fn(func)), &req, ctx)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bucket.object("response").unwrap()).unwrap()
+    }
+
+    #[runtime::test]
+    async fn successful_requests() {
+        let res = respond("Create", json!({ "Name": "a" })).await;
+        assert_eq!(ResponseStatus::Success, res.status);
+        assert_eq!("id-a", res.physical_resource_id);
+        assert_eq!("unique-id", res.request_id);
+        assert_eq!("Resource", res.logical_resource_id);
+        assert_eq!(Some(json!({ "Arn": "arn:aws:example" })), res.data);
+        assert_eq!(None, res.reason);
+
+        let res = respond("Update", json!({ "Name": "new" })).await;
+        assert_eq!(ResponseStatus::Success, res.status);
+        assert_eq!("id-existing", res.physical_resource_id);
+        assert!(res.no_echo);
+
+        let res = respond("Delete", json!({ "Name": "a" })).await;
+        assert_eq!(ResponseStatus::Success, res.status);
+    }
+
+    #[runtime::test]
+    async fn failed_requests() {
+        let res = respond("Create", json!({ "Name": "a", "Fail": "error" })).await;
+        assert_eq!(ResponseStatus::Failed, res.status);
+        let reason = res.reason.unwrap();
+        assert!(reason.contains("cannot create a"), "{}", reason);
+        assert_eq!(None, res.data);
+
+        let res = respond("Create", json!({ "Name": "a", "Fail": "panic" })).await;
+        assert_eq!(ResponseStatus::Failed, res.status);
+        assert!(res.reason.unwrap().starts_with("Panic: cannot create a"));
+
+        let res = respond("Delete", json!({ "Unexpected": true })).await;
+        assert_eq!(ResponseStatus::Failed, res.status);
+        assert_eq!("id-existing", res.physical_resource_id);
+    }
+
+    #[runtime::test]
+    async fn unknown_response_url() {
+        let ctx = testing::CtxBuilder::new().build();
+        let res = testing::invoke(adapter(handler_fn(func)), b"{}", ctx).await;
+        assert!(res.is_err());
+    }
+
+    #[runtime::test]
+    async fn without_ctx() {
+        // Without a context, there is no deadline to cancel the handler at.
+        let bucket = ResponseBucket::start().unwrap();
+        let req = request(&bucket, "Create", json!({ "Name": "a", "Fail": "sleep" }));
+        let req = serde_json::from_slice(&req).unwrap();
+        adapter(handler_fn(func)).call(req, None).await.unwrap();
+        let res: CustomResourceResponse =
+            serde_json::from_slice(&bucket.object("response").unwrap()).unwrap();
+        assert_eq!(ResponseStatus::Success, res.status);
+        assert_eq!("id-a", res.physical_resource_id);
+    }
+}
@@ -0,0 +1,435 @@
+use crate::{
+    client::Client, error_hook::ErrorReport, testing, ClientContext, CognitoIdentity, Config, Err,
+};
+use bytes::Bytes;
+use futures01::{sync::oneshot, Future, Stream};
+use http::{
+    header::HeaderName, HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Uri,
+};
+use hyper::{service::service_fn, Body, Server};
+use std::{
+    collections::{HashMap, VecDeque},
+    env,
+    net::SocketAddr,
+    panic,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread,
+    time::Duration,
+};
+
+/// An event queued on an [`Emulator`], along with the headers it is delivered with.
+///
+/// Unless overridden, each invocation has a unique request ID, a deadline three seconds
+/// after it is created, and the ARN of a function named `emulated`.
+#[derive(Debug, Clone)]
+pub struct Invocation {
+    id: String,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl Invocation {
+    /// Returns an invocation of the function with `body` as its payload.
+    #[must_use]
+    pub fn new<B: Into<Bytes>>(body: B) -> Self {
+        let id = testing::request_id();
+        Self {
+            id: id.clone(),
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+        .request_id(id)
+        .timeout(Duration::from_secs(3))
+        .function_arn("arn:aws:lambda:us-east-1:123456789012:function:emulated")
+    }
+
+    /// Returns an invocation whose payload is `event` serialized as JSON.
+    ///
+    /// # Panics
+    /// Panics if `event` cannot be serialized.
+    #[must_use]
+    pub fn json<T: serde::Serialize>(event: &T) -> Self {
+        let body = serde_json::to_vec(event).expect("Event should serialize to JSON");
+        Self::new(body)
+    }
+
+    /// The request ID of the invocation, used to look up its outcome on the [`Emulator`].
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Sets the request ID of the invocation.
+    #[must_use]
+    pub fn request_id<S: Into<String>>(mut self, id: S) -> Self {
+        self.id = id.into();
+        let id = self.id.clone();
+        self.header("lambda-runtime-aws-request-id", &id)
+    }
+
+    /// Sets the deadline of the invocation, in Unix time milliseconds.
+    #[must_use]
+    pub fn deadline(self, deadline: u64) -> Self {
+        self.header("lambda-runtime-deadline-ms", &deadline.to_string())
+    }
+
+    /// Sets the deadline of the invocation to `timeout` from now.
+    #[must_use]
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.deadline(testing::deadline_in(timeout))
+    }
+
+    /// Sets the ARN of the function being invoked.
+    #[must_use]
+    pub fn function_arn(self, arn: &str) -> Self {
+        self.header("lambda-runtime-invoked-function-arn", arn)
+    }
+
+    /// Sets the X-Ray trace ID of the invocation.
+    #[must_use]
+    pub fn trace_id(self, trace_id: &str) -> Self {
+        self.header("lambda-runtime-trace-id", trace_id)
+    }
+
+    /// Sets the client context sent by the AWS Mobile SDK.
+    ///
+    /// # Panics
+    /// Panics if `ctx` cannot be serialized.
+    #[must_use]
+    pub fn client_context(self, ctx: &ClientContext) -> Self {
+        let ctx = serde_json::to_string(ctx).expect("ClientContext should serialize to JSON");
+        self.header("lambda-runtime-client-context", &ctx)
+    }
+
+    /// Sets the Cognito identity that made the invocation.
+    ///
+    /// # Panics
+    /// Panics if `identity` cannot be serialized.
+    #[must_use]
+    pub fn identity(self, identity: &CognitoIdentity) -> Self {
+        let identity =
+            serde_json::to_string(identity).expect("CognitoIdentity should serialize to JSON");
+        self.header("lambda-runtime-cognito-identity", &identity)
+    }
+
+    /// Sets an arbitrary header, replacing any previous value. This can be used to deliver
+    /// malformed values of the headers above.
+    ///
+    /// # Panics
+    /// Panics if `name` or `value` are not valid in an HTTP header.
+    #[must_use]
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("Header name should be valid");
+        let value = HeaderValue::from_str(value).expect("Header value should be valid");
+        self.headers.insert(name, value);
+        self
+    }
+}
+
+/// The outcome of an invocation, as reported by the runtime.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// The function's response, as posted to `/runtime/invocation/{id}/response`.
+    Response(Bytes),
+    /// The report posted to `/runtime/invocation/{id}/error`.
+    Error(ErrorReport),
+}
+
+#[derive(Debug, Default)]
+struct State {
+    events: VecDeque<Invocation>,
+    outcomes: HashMap<String, Outcome>,
+    init_error: Option<ErrorReport>,
+    failure: Option<String>,
+}
+
+/// A local server implementing the [Lambda Runtime
+/// API](https://docs.aws.amazon.com/lambda/latest/dg/runtimes-api.html), for end-to-end tests
+/// of a function.
+///
+/// Queued [`Invocation`]s are delivered in order from `/runtime/invocation/next`. Once the
+/// queue is empty, that endpoint responds with `410 Gone`, stopping the runtime so that a
+/// test can inspect the [`Outcome`] of each invocation. The server stops when the emulator
+/// is dropped, and if it fails, inspecting the emulator panics with its error.
+///
+/// # Example
+/// ```
+/// #![feature(async_await)]
+///
+/// use lambda::{
+///     emulator::{Emulator, Invocation, Outcome},
+///     handler_fn, LambdaCtx,
+/// };
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// #[runtime::main]
+/// async fn main() -> Result<(), Err> {
+///     let emulator = Emulator::start()?;
+///     let invocation = Invocation::new(r#""hello""#);
+///     let id = invocation.id().to_string();
+///     emulator.push(invocation);
+///
+///     let _ = lambda::run_with_client(emulator.client(), emulator.config(), handler_fn(func)).await;
+///
+///     assert_eq!(Some(Outcome::Response(r#""hello""#.into())), emulator.outcome(&id));
+///     Ok(())
+/// }
+///
+/// async fn func(event: String, _ctx: Option<LambdaCtx>) -> Result<String, Err> {
+///     Ok(event)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Emulator {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl Emulator {
+    /// Starts an emulator listening on an unused local port.
+    ///
+    /// # Errors
+    /// Returns an error if the server could not bind to a port.
+    pub fn start() -> Result<Self, Err> {
+        let state = Arc::new(Mutex::new(State::default()));
+        let server = Server::try_bind(&([127, 0, 0, 1], 0).into())?;
+        let shared = Arc::clone(&state);
+        let server = server.serve(move || {
+            let state = Arc::clone(&shared);
+            service_fn(move |req| route(&state, req))
+        });
+        let addr = server.local_addr();
+
+        let (shutdown, signal) = oneshot::channel();
+        let failed = Arc::clone(&state);
+        let server = server
+            .with_graceful_shutdown(signal)
+            .map_err(move |err| lock(&failed).failure = Some(err.to_string()));
+        thread::spawn(move || hyper::rt::run(server));
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// The address the emulator is listening on.
+    #[must_use]
+    pub const fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns a client connected to the emulator, for use with
+    /// [`run_with_client`](crate::run_with_client).
+    ///
+    /// # Panics
+    /// Panics if the address of the emulator is not a valid `Uri`, which should not happen.
+    #[must_use]
+    pub fn client(&self) -> Client {
+        let uri = format!("http://{}", self.addr)
+            .parse::<Uri>()
+            .expect("Emulator address should be a valid Uri");
+        Client::new(uri)
+    }
+
+    /// Returns the configuration of the emulated function, for use with
+    /// [`run_with_client`](crate::run_with_client).
+    #[must_use]
+    pub fn config(&self) -> Config {
+        Config {
+            endpoint: self.addr.to_string(),
+            function_name: String::from("emulated"),
+            memory: 128,
+            version: String::from("$LATEST"),
+            log_stream: String::from("emulated"),
+            log_group: String::from("/aws/lambda/emulated"),
+        }
+    }
+
+    /// Points `AWS_LAMBDA_RUNTIME_API` at the emulator and sets the other environment
+    /// variables that Lambda provides to a function, so that [`run`](crate::run) can be used.
+    ///
+    /// The environment is shared by every test in the process, so tests that can should use
+    /// [`run_with_client`](crate::run_with_client) with [`config`](Emulator::config) instead.
+    pub fn set_env(&self) {
+        let config = self.config();
+        env::set_var("AWS_LAMBDA_RUNTIME_API", config.endpoint);
+        env::set_var("AWS_LAMBDA_FUNCTION_NAME", config.function_name);
+        env::set_var("AWS_LAMBDA_FUNCTION_MEMORY_SIZE", config.memory.to_string());
+        env::set_var("AWS_LAMBDA_FUNCTION_VERSION", config.version);
+        env::set_var("AWS_LAMBDA_LOG_STREAM_NAME", config.log_stream);
+        env::set_var("AWS_LAMBDA_LOG_GROUP_NAME", config.log_group);
+    }
+
+    /// Queues `invocation` for delivery to the runtime.
+    pub fn push(&self, invocation: Invocation) {
+        lock(&self.state).events.push_back(invocation);
+    }
+
+    /// Returns the outcome of the invocation with the request ID `id`, or `None` if the
+    /// runtime has not reported one.
+    ///
+    /// # Panics
+    /// Panics if the server has failed.
+    #[must_use]
+    pub fn outcome(&self, id: &str) -> Option<Outcome> {
+        self.state().outcomes.remove(id)
+    }
+
+    /// Returns the report posted to `/runtime/init/error`, if any.
+    ///
+    /// # Panics
+    /// Panics if the server has failed.
+    #[must_use]
+    pub fn init_error(&self) -> Option<ErrorReport> {
+        self.state().init_error.take()
+    }
+
+    /// Locks the state of the emulator, failing the test if the server has stopped serving
+    /// the runtime.
+    fn state(&self) -> MutexGuard<'_, State> {
+        let state = lock(&self.state);
+        if let Some(err) = &state.failure {
+            panic::panic_any(format!("Runtime API emulator failed: {err}"));
+        }
+        state
+    }
+}
+
+impl Drop for Emulator {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+fn route(
+    state: &Arc<Mutex<State>>,
+    req: Request<Body>,
+) -> impl Future<Item = Response<Body>, Error = hyper::Error> {
+    let state = Arc::clone(state);
+    let (parts, body) = req.into_parts();
+    body.concat2().map(move |body| {
+        let segments: Vec<&str> = parts.uri.path().trim_matches('/').split('/').collect();
+        let status = match (&parts.method, segments.as_slice()) {
+            (&Method::GET, ["runtime", "invocation", "next"]) => {
+                let next = lock(&state).events.pop_front();
+                match next {
+                    Some(invocation) => {
+                        let mut res = Response::new(Body::from(invocation.body));
+                        *res.headers_mut() = invocation.headers;
+                        return res;
+                    }
+                    None => StatusCode::GONE,
+                }
+            }
+            (&Method::POST, ["runtime", "invocation", id, "response"]) => {
+                let outcome = Outcome::Response(body.into_bytes());
+                lock(&state).outcomes.insert(String::from(*id), outcome);
+                StatusCode::ACCEPTED
+            }
+            (&Method::POST, ["runtime", "invocation", id, "error"]) => {
+                serde_json::from_slice(&body).map_or(StatusCode::BAD_REQUEST, |report| {
+                    let outcome = Outcome::Error(report);
+                    lock(&state).outcomes.insert(String::from(*id), outcome);
+                    StatusCode::ACCEPTED
+                })
+            }
+            (&Method::POST, ["runtime", "init", "error"]) => {
+                serde_json::from_slice(&body).map_or(StatusCode::BAD_REQUEST, |report| {
+                    lock(&state).init_error = Some(report);
+                    StatusCode::ACCEPTED
+                })
+            }
+            _ => StatusCode::NOT_FOUND,
+        };
+        let mut res = Response::new(Body::empty());
+        *res.status_mut() = status;
+        res
+    })
+}
+
+/// Locks `state`, ignoring poisoning: a panic in a test should not hide the outcomes
+/// recorded before it.
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lock, Emulator, Invocation, Outcome};
+    use crate::{handler_fn, Err, LambdaCtx, Runtime};
+
+    async fn func(event: String, ctx: Option<LambdaCtx>) -> Result<String, Err> {
+        let ctx = ctx.unwrap();
+        match event.as_str() {
+            "trace" => Ok(ctx.xray_trace_id.unwrap_or_default()),
+            "fail" => Err("failed".into()),
+            _ => Ok(event),
+        }
+    }
+
+    #[runtime::test]
+    async fn outcomes() -> Result<(), Err> {
+        let emulator = Emulator::start()?;
+        let trace =
+            Invocation::new(r#""trace""#).trace_id("Root=1-5759e988-bd862e3fe1be46a994272793");
+        let fail = Invocation::new(r#""fail""#);
+        let (trace_id, fail_id) = (trace.id().to_string(), fail.id().to_string());
+        emulator.push(trace);
+        emulator.push(fail);
+
+        let res = Runtime::new()
+            .run_with_client(emulator.client(), emulator.config(), handler_fn(func))
+            .await;
+        assert!(res.is_err());
+
+        let expected = r#""Root=1-5759e988-bd862e3fe1be46a994272793""#;
+        assert_eq!(
+            Some(Outcome::Response(expected.into())),
+            emulator.outcome(&trace_id)
+        );
+        match emulator.outcome(&fail_id) {
+            Some(Outcome::Error(report)) => assert_eq!("failed", report.err),
+            outcome => panic!("Expected an error report, got {:?}", outcome),
+        }
+        assert_eq!(None, emulator.init_error());
+        Ok(())
+    }
+
+    #[runtime::test]
+    async fn invalid_headers() -> Result<(), Err> {
+        let emulator = Emulator::start()?;
+        let invalid = Invocation::new(r#""hello""#).header("lambda-runtime-deadline-ms", "soon");
+        let next = Invocation::new(r#""next""#);
+        let (invalid_id, next_id) = (invalid.id().to_string(), next.id().to_string());
+        emulator.push(invalid);
+        emulator.push(next);
+
+        let res = Runtime::new()
+            .run_with_client(emulator.client(), emulator.config(), handler_fn(func))
+            .await;
+        assert!(res.is_err());
+        assert!(matches!(
+            emulator.outcome(&invalid_id),
+            Some(Outcome::Error(_))
+        ));
+        assert_eq!(
+            Some(Outcome::Response(r#""next""#.into())),
+            emulator.outcome(&next_id)
+        );
+        assert_eq!(None, emulator.init_error());
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "Runtime API emulator failed: connection reset")]
+    fn server_failure() {
+        let emulator = Emulator::start().unwrap();
+        lock(&emulator.state).failure = Some(String::from("connection reset"));
+        let _ = emulator.outcome("1");
+    }
+}
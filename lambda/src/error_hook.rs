@@ -1,60 +1,382 @@
 use std::{
-    mem, ptr,
-    sync::atomic::{AtomicPtr, Ordering},
+    any::{self, Any},
+    backtrace::{Backtrace, BacktraceStatus},
+    cell::RefCell,
+    error::{self, Error},
+    mem, panic, ptr,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Once,
+    },
 };
 
 use serde::{Deserialize, Serialize};
 
-use crate::Err;
+use crate::{Err, LambdaCtx};
 
 static HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+static PANIC_HOOK: Once = Once::new();
 
-/// A computer-readable report of an unhandled error.
+thread_local! {
+    /// The location and backtrace of the most recent panic on this thread, as recorded by
+    /// the panic hook.
+    static PANIC_LOCATION: RefCell<Option<(String, Vec<String>)>> = const { RefCell::new(None) };
+}
+
+/// A computer-readable report of an unhandled error, serialized in the
+/// [format](https://docs.aws.amazon.com/lambda/latest/dg/runtimes-api.html#runtimes-api-invokeerror)
+/// expected by the Lambda APIs.
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct ErrorReport {
     /// The type of the error passed to the Lambda APIs.
+    #[serde(rename = "errorType")]
     pub name: String,
     /// The [std::fmt::Display] output of the error.
+    #[serde(rename = "errorMessage")]
     pub err: String,
+    /// The errors that caused this one, followed by the frames of the backtrace, if any.
+    #[serde(rename = "stackTrace", default)]
+    pub stack_trace: Vec<String>,
 }
 
-fn default_error_hook(err: Err) -> ErrorReport {
-    ErrorReport {
-        name: String::from("UnknownError"),
-        err: format!("{}", err),
+impl ErrorReport {
+    /// Creates a report named `name` for `err`.
+    ///
+    /// The stack trace is filled from the chain of [`source`](Error::source)s of `err`,
+    /// followed by the backtrace it provides, if one was captured.
+    pub fn new<N: Into<String>>(name: N, err: &(dyn Error + 'static)) -> Self {
+        let mut stack_trace = vec![];
+        let mut source = err.source();
+        while let Some(err) = source {
+            stack_trace.push(format!("caused by: {err}"));
+            source = err.source();
+        }
+        if let Some(backtrace) = error::request_ref::<Backtrace>(err) {
+            stack_trace.extend(backtrace_frames(backtrace));
+        }
+        Self {
+            name: name.into(),
+            err: format!("{err}"),
+            stack_trace,
+        }
     }
 }
 
+/// Splits a captured backtrace into lines, or returns none if it was not captured.
+fn backtrace_frames(backtrace: &Backtrace) -> Vec<String> {
+    if backtrace.status() != BacktraceStatus::Captured {
+        return vec![];
+    }
+    backtrace
+        .to_string()
+        .lines()
+        .map(|line| String::from(line.trim()))
+        .collect()
+}
+
+/// A copy of the message and chain of sources of an error, reported in place of an error
+/// that is still needed afterwards.
+#[derive(Debug)]
+pub(crate) struct ErrorCopy {
+    message: String,
+    source: Option<Box<Self>>,
+}
+
+impl ErrorCopy {
+    pub(crate) fn new(err: &(dyn Error + 'static)) -> Self {
+        Self {
+            message: err.to_string(),
+            source: err.source().map(|source| Box::new(Self::new(source))),
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCopy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for ErrorCopy {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+fn default_error_hook(err: Err) -> ErrorReport {
+    ErrorReport::new("UnknownError", &*err)
+}
+
 /// Transforms
 ///
 /// This function is called by the Lambda Runtime if an error is returned from a Handler.
 /// This implementation is a near-direct copy of [`std::alloc::set_alloc_error_hook`], down
 /// to the transmute operation.
-pub(crate) fn generate_report(err: Err) -> ErrorReport {
+///
+/// Without a custom hook, the report is named `error_type`, if the type of the error is known.
+pub(crate) fn generate_report(err: Err, error_type: Option<String>) -> ErrorReport {
     let hook = HOOK.load(Ordering::SeqCst);
-    let hook: fn(Err) -> ErrorReport = if hook.is_null() {
-        default_error_hook
-    } else {
-        unsafe { mem::transmute(hook) }
-    };
+    if hook.is_null() {
+        return match error_type {
+            Some(name) => ErrorReport::new(name, &*err),
+            None => default_error_hook(err),
+        };
+    }
+    let hook: fn(Err) -> ErrorReport = unsafe { mem::transmute(hook) };
     hook(err)
 }
 
+/// Transforms `err` into a report using the error hook, named after the type of `err` if
+/// no hook is registered.
+pub(crate) fn report_error<E: Into<Err>>(err: E) -> ErrorReport {
+    let (err, error_type) = resolve_error_type(err);
+    generate_report(err, error_type)
+}
+
+/// Names the `errorType` reported when an error is returned by a [`Handler`](crate::Handler).
+///
+/// Without a custom [`ErrorReporter`] or hook, errors are reported using the name of their
+/// type as returned by [`std::any::type_name`], without its path or generic parameters, so a
+/// `Box<std::num::ParseIntError>` is reported as `ParseIntError`. Errors returned as a boxed
+/// trait object are only named if their type is known: either a common error of the standard
+/// library or `serde_json`, or an error that provides itself as a `dyn ErrorType` from
+/// [`Error::provide`]. String errors, and errors of unknown types, are reported as
+/// `UnknownError`; their [`source`](Error::source)s still appear in the stack trace.
+///
+/// Implementing this trait and providing it overrides the name, such as to report the variant
+/// of an enum.
+///
+/// # Example
+/// ```
+/// #![feature(error_generic_member_access)]
+///
+/// use lambda::error_hook::ErrorType;
+/// use std::error::{Error, Request};
+///
+/// #[derive(Debug)]
+/// enum StoreError {
+///     NotFound,
+///     Throttled,
+/// }
+///
+/// impl ErrorType for StoreError {
+///     fn error_type(&self) -> String {
+///         match self {
+///             StoreError::NotFound => String::from("NotFound"),
+///             StoreError::Throttled => String::from("Throttled"),
+///         }
+///     }
+/// }
+///
+/// impl Error for StoreError {
+///     fn provide<'a>(&'a self, request: &mut Request<'a>) {
+///         request.provide_ref::<dyn ErrorType>(self);
+///     }
+/// }
+/// # impl std::fmt::Display for StoreError {
+/// #     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// #         write!(f, "{self:?}")
+/// #     }
+/// # }
+/// ```
+pub trait ErrorType {
+    /// Returns the `errorType` to report for this error, which is the name of its type by
+    /// default.
+    fn error_type(&self) -> String {
+        type_name::<Self>().unwrap_or("UnknownError").to_owned()
+    }
+}
+
+/// Errors whose type is recognized when they are boxed.
+const KNOWN_ERRORS: &[fn(&(dyn Error + 'static)) -> Option<&'static str>] = &[
+    known::<std::io::Error>,
+    known::<std::num::ParseIntError>,
+    known::<std::num::ParseFloatError>,
+    known::<std::num::TryFromIntError>,
+    known::<std::str::ParseBoolError>,
+    known::<std::str::Utf8Error>,
+    known::<std::string::FromUtf8Error>,
+    known::<std::net::AddrParseError>,
+    known::<std::fmt::Error>,
+    known::<serde_json::Error>,
+];
+
+/// Returns the name of `E` if `err` is one.
+fn known<E: Error + 'static>(err: &(dyn Error + 'static)) -> Option<&'static str> {
+    err.downcast_ref::<E>().and_then(|_| type_name::<E>())
+}
+
+/// Returns the name of `T` without its path, generic parameters or pointers to it, or `None`
+/// if it is a trait object or a string.
+fn type_name<T: ?Sized>() -> Option<&'static str> {
+    let mut name = any::type_name::<T>();
+    let strings = [
+        any::type_name::<String>(),
+        any::type_name::<&str>(),
+        any::type_name::<std::borrow::Cow<'_, str>>(),
+        any::type_name::<crate::StringError>(),
+    ];
+    loop {
+        if strings.contains(&name) || name.starts_with("dyn ") {
+            return None;
+        }
+        if let Some(pointee) = name.strip_prefix('&') {
+            name = pointee.trim_start_matches("mut ");
+            continue;
+        }
+        let pointers = ["alloc::boxed::Box<", "alloc::sync::Arc<", "alloc::rc::Rc<"];
+        match pointers
+            .iter()
+            .find_map(|pointer| name.strip_prefix(pointer))
+        {
+            Some(pointee) => name = pointee.strip_suffix('>').unwrap_or(pointee),
+            None => break,
+        }
+    }
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next()
+}
+
+/// Converts `err` into an [`Err`], along with the `errorType` to report for it, if known.
+pub(crate) fn resolve_error_type<E: Into<Err>>(err: E) -> (Err, Option<String>) {
+    let err = err.into();
+    let provided = |err: &(dyn Error + 'static)| {
+        error::request_ref::<dyn ErrorType>(err).map(ErrorType::error_type)
+    };
+    // The static type of `err` is only informative if it is not a trait object.
+    let error_type = provided(&*err)
+        .or_else(|| type_name::<E>().map(String::from))
+        .or_else(|| {
+            let name = KNOWN_ERRORS.iter().find_map(|known| known(&*err));
+            name.map(String::from)
+        });
+    (err, error_type)
+}
+
+/// Transforms errors into [`ErrorReport`]s for a single [`Runtime`](crate::Runtime).
+///
+/// Unlike the hook registered with [`set_error_hook`], a reporter can hold state, such as a
+/// metrics client or the name of the environment, and is only used by the runtime it is
+/// attached to. `ErrorReporter` is implemented for closures taking the same arguments as
+/// [`ErrorReporter::report`].
+///
+/// # Example
+/// ```no_run
+/// #![feature(async_await)]
+///
+/// use lambda::{error_hook::ErrorReport, handler_fn, LambdaCtx, Runtime};
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// #[runtime::main]
+/// async fn main() -> Result<(), Err> {
+///     let stage = std::env::var("STAGE")?;
+///     let reporter = move |err: Err, _ctx: Option<&LambdaCtx>| {
+///         ErrorReport::new(format!("{}Error", stage), &*err)
+///     };
+///     let func = handler_fn(func);
+///     Runtime::new().error_reporter(reporter).run(func).await?;
+///     Ok(())
+/// }
+///
+/// async fn func(event: String, _ctx: Option<LambdaCtx>) -> Result<String, Err> {
+///     Ok(event)
+/// }
+/// ```
+pub trait ErrorReporter: Send + Sync {
+    /// Transforms `err` into a report.
+    ///
+    /// `ctx` is the context of the invocation that failed, or `None` if the error occurred
+    /// before the invocation's context was available, such as during initialization.
+    fn report(&self, err: Err, ctx: Option<&LambdaCtx>) -> ErrorReport;
+}
+
+impl<F> ErrorReporter for F
+where
+    F: Fn(Err, Option<&LambdaCtx>) -> ErrorReport + Send + Sync,
+{
+    fn report(&self, err: Err, ctx: Option<&LambdaCtx>) -> ErrorReport {
+        self(err, ctx)
+    }
+}
+
+/// An [`ErrorReporter`] which delegates to the hook registered with [`set_error_hook`],
+/// as a runtime without a reporter does.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HookReporter;
+
+impl ErrorReporter for HookReporter {
+    fn report(&self, err: Err, _ctx: Option<&LambdaCtx>) -> ErrorReport {
+        report_error(err)
+    }
+}
+
+/// Installs a panic hook that records where a panic occurred and its backtrace, as neither
+/// is part of the payload handed to [`std::panic::catch_unwind`].
+///
+/// The previously installed hook is still invoked, so panics continue to be logged.
+pub(crate) fn capture_panic_locations() {
+    PANIC_HOOK.call_once(|| {
+        let prev = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let location = info.location().map(ToString::to_string);
+            let frames = backtrace_frames(&Backtrace::capture());
+            PANIC_LOCATION.with(|loc| *loc.borrow_mut() = location.map(|l| (l, frames)));
+            prev(info);
+        }));
+    });
+}
+
+/// Transforms the payload of a panic caught while running a Handler into an [`ErrorReport`].
+///
+/// Panics are always reported with the name `Panic`, bypassing the custom error hook, as
+/// the payload is not an error.
+pub(crate) fn panic_report(payload: &(dyn Any + Send + 'static)) -> ErrorReport {
+    let msg = payload
+        .downcast_ref::<&str>()
+        .map(|msg| String::from(*msg))
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("Box<Any>"));
+    let (err, stack_trace) = match PANIC_LOCATION.with(|loc| loc.borrow_mut().take()) {
+        Some((location, frames)) => (format!("{msg} at {location}"), frames),
+        None => (msg, vec![]),
+    };
+    ErrorReport {
+        name: String::from("Panic"),
+        err,
+        stack_trace,
+    }
+}
+
+/// Creates the [`ErrorReport`] for a Handler that was cancelled because it was about to
+/// exceed the invocation's `deadline`.
+pub(crate) fn timeout_report(deadline: u64) -> ErrorReport {
+    ErrorReport {
+        name: String::from("TimeoutError"),
+        err: format!("Task was cancelled before its deadline of {deadline} ms"),
+        stack_trace: vec![],
+    }
+}
+
 /// Registers a custom error hook, replacing any that was previously registered.
 ///
+/// The hook is shared by every runtime in the process that does not have its own
+/// [`ErrorReporter`], and cannot capture state. New code should prefer an `ErrorReporter`.
+///
 /// The Lambda error hook is invoked when a [`Handler`] or [`HttpHandler`] returns an error, but prior
 /// to the runtime reporting the error to the Lambda Runtime APIs. This hook is intended to be used
 /// by those interested in programatialy
 ///
 /// This function, in terms of intended usage and implementation, mimics [`std::alloc::set_alloc_error_hook`].
-/// 
+///
 /// # Example
 /// ```
 /// #![feature(async_await)]
-/// 
+///
 /// use lambda::{handler_fn, error_hook, LambdaCtx};
 /// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
-/// 
+///
 /// #[runtime::main]
 /// async fn main() -> Result<(), Err> {
 ///     let func = handler_fn(func);
@@ -68,10 +390,7 @@ pub(crate) fn generate_report(err: Err) -> ErrorReport {
 /// }
 ///
 /// fn error_hook(e: Err) -> error_hook::ErrorReport {
-///     error_hook::ErrorReport{
-///         name: String::from("CustomError"),
-///         err: format!("{}", e),
-///     }
+///     error_hook::ErrorReport::new("CustomError", &*e)
 /// }
 /// ```
 pub fn set_error_hook<E: Into<Err>>(hook: fn(err: E) -> ErrorReport) {
@@ -83,16 +402,182 @@ fn set_err_hook() {
     use crate::err_fmt;
     set_error_hook(|err: Err| {
         if let Some(e) = err.downcast_ref::<std::io::Error>() {
-            ErrorReport {
-                name: String::from("std::io::Error"),
-                err: format!("{}", e),
-            }
+            ErrorReport::new("std::io::Error", e)
         } else {
             default_error_hook(err)
         }
     });
 
     let e = err_fmt!("An error");
-    let e = generate_report(e.into());
+    let e = generate_report(e.into(), None);
     assert_eq!(String::from("UnknownError"), e.name)
 }
+
+#[test]
+fn panic_payload_report() {
+    capture_panic_locations();
+    let payload = panic::catch_unwind(|| panic!("bad input: {}", 42)).unwrap_err();
+    let report = panic_report(&*payload);
+    assert_eq!(String::from("Panic"), report.name);
+    assert!(report.err.starts_with("bad input: 42 at "));
+    assert!(report.err.contains("error_hook.rs"));
+}
+
+#[test]
+fn stateful_reporter() {
+    use crate::err_fmt;
+    let stage = String::from("Prod");
+    let reporter: Box<dyn ErrorReporter> =
+        Box::new(move |err: Err, ctx: Option<&LambdaCtx>| ErrorReport {
+            name: format!("{}Error", stage),
+            err: format!("{}: {}", ctx.map_or("init", |ctx| &ctx.id), err),
+            stack_trace: vec![],
+        });
+
+    let ctx = LambdaCtx {
+        id: String::from("request-1"),
+        ..Default::default()
+    };
+    let report = reporter.report(err_fmt!("An error").into(), Some(&ctx));
+    assert_eq!(String::from("ProdError"), report.name);
+    assert_eq!(String::from("request-1: An error"), report.err);
+}
+
+#[runtime::test]
+async fn hook_reporter_error_type() {
+    use crate::{handler_fn, testing, Runtime};
+
+    use std::num::ParseIntError;
+
+    async fn parse(event: String, _ctx: Option<LambdaCtx>) -> Result<i32, ParseIntError> {
+        event.parse()
+    }
+
+    let ctx = testing::CtxBuilder::new().build();
+    let runtime = Runtime::new();
+    let default = testing::invoke_with(&runtime, handler_fn(parse), b"\"x\"", ctx.clone());
+    let runtime = Runtime::new().error_reporter(HookReporter);
+    let hook = testing::invoke_with(&runtime, handler_fn(parse), b"\"x\"", ctx);
+    let (default, hook) = (default.await.unwrap_err(), hook.await.unwrap_err());
+    assert_eq!(default.name, hook.name);
+    assert_eq!(default.err, hook.err);
+}
+
+#[test]
+fn report_source_chain() {
+    use crate::err_fmt;
+
+    #[derive(Debug)]
+    struct Outer(crate::StringError);
+
+    impl std::fmt::Display for Outer {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("request failed")
+        }
+    }
+
+    impl Error for Outer {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    let report = ErrorReport::new("RequestError", &Outer(err_fmt!("connection reset")));
+    assert_eq!(
+        vec![String::from("caused by: connection reset")],
+        report.stack_trace
+    );
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!("RequestError", json["errorType"]);
+    assert_eq!("request failed", json["errorMessage"]);
+    assert_eq!("caused by: connection reset", json["stackTrace"][0]);
+}
+
+#[test]
+fn derived_error_type() {
+    use std::{
+        error::Request,
+        fmt::{self, Display},
+    };
+
+    #[derive(Debug)]
+    struct Custom;
+
+    impl Display for Custom {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("custom")
+        }
+    }
+
+    impl Error for Custom {
+        fn provide<'a>(&'a self, request: &mut Request<'a>) {
+            request.provide_ref::<dyn ErrorType>(self);
+        }
+    }
+
+    impl ErrorType for Custom {
+        fn error_type(&self) -> String {
+            String::from("CustomError")
+        }
+    }
+
+    let err = "x".parse::<i32>().unwrap_err();
+    assert_eq!(
+        Some(String::from("ParseIntError")),
+        resolve_error_type(err).1
+    );
+    assert_eq!(
+        Some(String::from("CustomError")),
+        resolve_error_type(Custom).1
+    );
+    let err: Err = Box::new(Custom);
+    assert_eq!(Some(String::from("CustomError")), resolve_error_type(err).1);
+}
+
+#[test]
+fn boxed_error_type() {
+    let err = Box::new("x".parse::<i32>().unwrap_err());
+    assert_eq!(
+        Some(String::from("ParseIntError")),
+        resolve_error_type(err).1
+    );
+    let err: Err = "x".parse::<f64>().unwrap_err().into();
+    assert_eq!(
+        Some(String::from("ParseFloatError")),
+        resolve_error_type(err).1
+    );
+
+    // Boxed errors of unknown types are not named after the errors they wrap.
+    #[derive(Debug)]
+    struct Wrapper(std::num::ParseIntError);
+
+    impl std::fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("wrapped")
+        }
+    }
+
+    impl Error for Wrapper {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    let err: Err = Box::new(Wrapper("x".parse::<i32>().unwrap_err()));
+    assert_eq!(None, resolve_error_type(err).1);
+    let err = Wrapper("x".parse::<i32>().unwrap_err());
+    assert_eq!(Some(String::from("Wrapper")), resolve_error_type(err).1);
+}
+
+#[test]
+fn string_error_type() {
+    assert_eq!(None, resolve_error_type("failed").1);
+    assert_eq!(None, resolve_error_type(String::from("failed")).1);
+    let err: Err = "failed".into();
+    assert_eq!(None, resolve_error_type(err).1);
+    assert_eq!(None, resolve_error_type(crate::err_fmt!("failed")).1);
+    let report = report_error("failed");
+    assert_eq!("UnknownError", report.name);
+    assert_eq!("failed", report.err);
+}
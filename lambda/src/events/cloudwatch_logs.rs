@@ -0,0 +1,156 @@
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{de::Error as _, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+/// Log events delivered by a CloudWatch Logs [subscription
+/// filter](https://docs.aws.amazon.com/lambda/latest/dg/services-cloudwatchlogs.html).
+///
+/// The events arrive compressed with gzip and encoded as base64 under `awslogs.data`. They are
+/// decoded and decompressed when the event is deserialized, and compressed and encoded again
+/// when it is serialized.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogsEvent {
+    /// The decoded log events and the log stream they belong to.
+    pub data: LogsData,
+}
+
+/// A batch of log events from a single log stream.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LogsData {
+    /// The ID of the AWS account the log events were written in.
+    pub owner: String,
+    /// The name of the log group.
+    pub log_group: String,
+    /// The name of the log stream.
+    pub log_stream: String,
+    /// The names of the subscription filters that matched the log events.
+    pub subscription_filters: Vec<String>,
+    /// Whether the batch contains log events, or only checks that the function is reachable.
+    pub message_type: MessageType,
+    /// The log events, which are empty for control messages.
+    pub log_events: Vec<LogEntry>,
+}
+
+/// The kind of a batch of log events.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MessageType {
+    /// A batch of log events.
+    #[default]
+    DataMessage,
+    /// A check that the destination of the subscription is reachable, without log events.
+    ControlMessage,
+}
+
+/// A log event.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    /// The ID of the log event.
+    pub id: String,
+    /// When the log event was written, in Unix time milliseconds.
+    pub timestamp: i64,
+    /// The message of the log event.
+    pub message: String,
+    /// The fields extracted by the filter pattern, if it defines any.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extracted_fields: HashMap<String, String>,
+}
+
+/// The shape of a [`LogsEvent`] as it is sent.
+#[derive(Deserialize, Serialize)]
+struct RawLogsEvent {
+    awslogs: RawLogsData,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RawLogsData {
+    data: String,
+}
+
+impl<'de> Deserialize<'de> for LogsEvent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawLogsEvent::deserialize(deserializer)?;
+        let compressed = base64::decode(&raw.awslogs.data).map_err(D::Error::custom)?;
+        let mut json = vec![];
+        GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut json)
+            .map_err(D::Error::custom)?;
+        let data = serde_json::from_slice(&json).map_err(D::Error::custom)?;
+        Ok(Self { data })
+    }
+}
+
+impl Serialize for LogsEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let json = serde_json::to_vec(&self.data).map_err(S::Error::custom)?;
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&json).map_err(S::Error::custom)?;
+        let compressed = encoder.finish().map_err(S::Error::custom)?;
+        let raw = RawLogsEvent {
+            awslogs: RawLogsData {
+                data: base64::encode(&compressed),
+            },
+        };
+        raw.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LogsEvent, MessageType};
+    use crate::{handler_fn, testing, Err, LambdaCtx};
+
+    #[test]
+    fn deserialize_event() {
+        let event = include_str!("../../tests/events/cloudwatch-logs-event.json");
+        let event: LogsEvent = serde_json::from_str(event).unwrap();
+        let data = &event.data;
+        assert_eq!(MessageType::DataMessage, data.message_type);
+        assert_eq!("123456789012", data.owner);
+        assert_eq!("/aws/lambda/echo-nodejs", data.log_group);
+        assert_eq!(
+            "2019/03/13/[$LATEST]94fa867e5374431291a7fc14e2f56ae7",
+            data.log_stream
+        );
+        assert_eq!(
+            vec!["LambdaStream_cloudwatchlogs-node"],
+            data.subscription_filters
+        );
+        assert_eq!(2, data.log_events.len());
+        assert_eq!(1_552_518_348_220, data.log_events[0].timestamp);
+        assert!(data.log_events[0].message.starts_with("REPORT RequestId"));
+    }
+
+    async fn count(event: LogsEvent, _ctx: Option<LambdaCtx>) -> Result<usize, Err> {
+        Ok(event.data.log_events.len())
+    }
+
+    #[runtime::test]
+    async fn decode_on_invoke() {
+        let event = include_str!("../../tests/events/cloudwatch-logs-event.json");
+        let ctx = testing::CtxBuilder::new().build();
+        let res = testing::invoke(handler_fn(count), event.as_bytes(), ctx).await;
+        assert_eq!("2", res.unwrap());
+    }
+
+    #[test]
+    fn round_trip() {
+        let event = include_str!("../../tests/events/cloudwatch-logs-event.json");
+        let event: LogsEvent = serde_json::from_str(event).unwrap();
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(event, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn invalid_data() {
+        let not_gzip = r#"{"awslogs":{"data":"SGVsbG8="}}"#;
+        assert!(serde_json::from_str::<LogsEvent>(not_gzip).is_err());
+        let not_base64 = r#"{"awslogs":{"data":"not base64!"}}"#;
+        assert!(serde_json::from_str::<LogsEvent>(not_base64).is_err());
+    }
+}
@@ -0,0 +1,556 @@
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The prefixes of the `triggerSource`s of the events a [`CognitoEvent`] can hold.
+const TRIGGERS: &[&str] = &[
+    "PreSignUp",
+    "PostConfirmation",
+    "PreAuthentication",
+    "PostAuthentication",
+    "TokenGeneration",
+    "CustomMessage",
+    "UserMigration",
+    "DefineAuthChallenge",
+    "CreateAuthChallenge",
+    "VerifyAuthChallengeResponse",
+];
+
+/// An event sent by a Cognito user pool [Lambda
+/// trigger](https://docs.aws.amazon.com/cognito/latest/developerguide/cognito-user-identity-pools-working-with-aws-lambda-triggers.html).
+///
+/// Cognito expects the function to return the event it received, with its `response` filled
+/// in. A handler can take the event of its trigger, such as a [`PreSignUpEvent`], modify its
+/// `response`, and return it.
+///
+/// # Example
+/// ```
+/// #![feature(async_await)]
+///
+/// use lambda::{events::cognito::PreSignUpEvent, LambdaCtx};
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// async fn func(mut event: PreSignUpEvent, _ctx: Option<LambdaCtx>) -> Result<PreSignUpEvent, Err> {
+///     let email = event.request.user_attributes.get("email");
+///     if email.is_some_and(|email| email.ends_with("@example.com")) {
+///         event.response.auto_confirm_user = true;
+///         event.response.auto_verify_email = true;
+///     }
+///     Ok(event)
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(
+    rename_all = "camelCase",
+    bound(deserialize = "Request: Deserialize<'de>, Response: Deserialize<'de> + Default")
+)]
+pub struct CognitoTrigger<Request, Response> {
+    /// The version of the event format.
+    pub version: String,
+    /// What invoked the trigger, such as `PreSignUp_SignUp` or `TokenGeneration_RefreshTokens`.
+    pub trigger_source: String,
+    /// The region of the user pool.
+    pub region: String,
+    /// The ID of the user pool.
+    pub user_pool_id: String,
+    /// The name of the user, if known.
+    pub user_name: Option<String>,
+    /// The caller of the user pool API.
+    pub caller_context: CallerContext,
+    /// The details of the operation, depending on the trigger.
+    pub request: Request,
+    /// The response to the operation, to be filled in by the function.
+    #[serde(default, deserialize_with = "nullable")]
+    pub response: Response,
+}
+
+/// The caller of a Cognito user pool API.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CallerContext {
+    /// The version of the AWS SDK that made the request.
+    pub aws_sdk_version: String,
+    /// The ID of the app client.
+    pub client_id: String,
+}
+
+/// An event sent by any Cognito user pool trigger, identified by the prefix of its
+/// `triggerSource`.
+///
+/// Allows a single function to handle several triggers. Serializing the event returns it
+/// in the shape it was received in.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum CognitoEvent {
+    /// Sent before a user signs up.
+    PreSignUp(PreSignUpEvent),
+    /// Sent after a user is confirmed.
+    PostConfirmation(PostConfirmationEvent),
+    /// Sent before a user signs in.
+    PreAuthentication(PreAuthenticationEvent),
+    /// Sent after a user signs in.
+    PostAuthentication(PostAuthenticationEvent),
+    /// Sent before tokens are issued, with a `triggerSource` starting with `TokenGeneration`.
+    PreTokenGeneration(PreTokenGenerationEvent),
+    /// Sent before a message such as a verification code is sent to a user.
+    CustomMessage(CustomMessageEvent),
+    /// Sent when an unknown user signs in or resets their password.
+    UserMigration(UserMigrationEvent),
+    /// Sent to choose the next challenge of a custom authentication flow.
+    DefineAuthChallenge(DefineAuthChallengeEvent),
+    /// Sent to create a challenge of a custom authentication flow.
+    CreateAuthChallenge(CreateAuthChallengeEvent),
+    /// Sent to verify the answer to a challenge of a custom authentication flow.
+    VerifyAuthChallengeResponse(VerifyAuthChallengeResponseEvent),
+}
+
+impl<'de> Deserialize<'de> for CognitoEvent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let event = Value::deserialize(deserializer)?;
+        let source = event.get("triggerSource").and_then(Value::as_str);
+        let source = source.ok_or_else(|| D::Error::missing_field("triggerSource"))?;
+        let event = match source.split('_').next().unwrap_or_default() {
+            "PreSignUp" => serde_json::from_value(event).map(Self::PreSignUp),
+            "PostConfirmation" => serde_json::from_value(event).map(Self::PostConfirmation),
+            "PreAuthentication" => serde_json::from_value(event).map(Self::PreAuthentication),
+            "PostAuthentication" => serde_json::from_value(event).map(Self::PostAuthentication),
+            "TokenGeneration" => serde_json::from_value(event).map(Self::PreTokenGeneration),
+            "CustomMessage" => serde_json::from_value(event).map(Self::CustomMessage),
+            "UserMigration" => serde_json::from_value(event).map(Self::UserMigration),
+            "DefineAuthChallenge" => serde_json::from_value(event).map(Self::DefineAuthChallenge),
+            "CreateAuthChallenge" => serde_json::from_value(event).map(Self::CreateAuthChallenge),
+            "VerifyAuthChallengeResponse" => {
+                serde_json::from_value(event).map(Self::VerifyAuthChallengeResponse)
+            }
+            _ => return Err(D::Error::unknown_variant(source, TRIGGERS)),
+        };
+        event.map_err(D::Error::custom)
+    }
+}
+
+/// Deserializes `null` as the default value of `T`, as Cognito sends `null` for the fields of
+/// a response that were not filled in.
+fn nullable<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Option::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
+/// The response to a trigger that does not expect one.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmptyResponse {}
+
+/// An event sent before a user signs up.
+pub type PreSignUpEvent = CognitoTrigger<PreSignUpRequest, PreSignUpResponse>;
+
+/// The details of a sign-up.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreSignUpRequest {
+    /// The attributes of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub user_attributes: HashMap<String, String>,
+    /// The validation data sent by the client.
+    #[serde(default, deserialize_with = "nullable")]
+    pub validation_data: HashMap<String, String>,
+    /// The client metadata passed to the API.
+    #[serde(default, deserialize_with = "nullable")]
+    pub client_metadata: HashMap<String, String>,
+}
+
+/// The response to a sign-up.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreSignUpResponse {
+    /// Whether to confirm the user without a confirmation code.
+    #[serde(default, deserialize_with = "nullable")]
+    pub auto_confirm_user: bool,
+    /// Whether to mark the email address of the user as verified.
+    #[serde(default, deserialize_with = "nullable")]
+    pub auto_verify_email: bool,
+    /// Whether to mark the phone number of the user as verified.
+    #[serde(default, deserialize_with = "nullable")]
+    pub auto_verify_phone: bool,
+}
+
+/// An event sent after a user is confirmed.
+pub type PostConfirmationEvent = CognitoTrigger<PostConfirmationRequest, EmptyResponse>;
+
+/// The details of a confirmation.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PostConfirmationRequest {
+    /// The attributes of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub user_attributes: HashMap<String, String>,
+    /// The client metadata passed to the API.
+    #[serde(default, deserialize_with = "nullable")]
+    pub client_metadata: HashMap<String, String>,
+}
+
+/// An event sent before a user signs in. Returning an error denies the sign-in.
+pub type PreAuthenticationEvent = CognitoTrigger<PreAuthenticationRequest, EmptyResponse>;
+
+/// The details of a sign-in attempt.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreAuthenticationRequest {
+    /// The attributes of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub user_attributes: HashMap<String, String>,
+    /// The validation data sent by the client.
+    #[serde(default, deserialize_with = "nullable")]
+    pub validation_data: HashMap<String, String>,
+    /// Whether the user does not exist, if the user pool reveals it.
+    #[serde(default)]
+    pub user_not_found: bool,
+}
+
+/// An event sent after a user signs in.
+pub type PostAuthenticationEvent = CognitoTrigger<PostAuthenticationRequest, EmptyResponse>;
+
+/// The details of a sign-in.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PostAuthenticationRequest {
+    /// The attributes of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub user_attributes: HashMap<String, String>,
+    /// Whether the user signed in from a new device.
+    #[serde(default)]
+    pub new_device_used: bool,
+    /// The client metadata passed to the API.
+    #[serde(default, deserialize_with = "nullable")]
+    pub client_metadata: HashMap<String, String>,
+}
+
+/// An event sent before tokens are issued.
+pub type PreTokenGenerationEvent =
+    CognitoTrigger<PreTokenGenerationRequest, PreTokenGenerationResponse>;
+
+/// The details of the tokens about to be issued.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreTokenGenerationRequest {
+    /// The attributes of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub user_attributes: HashMap<String, String>,
+    /// The groups and roles of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub group_configuration: GroupConfiguration,
+    /// The client metadata passed to the API.
+    #[serde(default, deserialize_with = "nullable")]
+    pub client_metadata: HashMap<String, String>,
+}
+
+/// The groups and IAM roles of a user.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupConfiguration {
+    /// The names of the groups of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub groups_to_override: Vec<String>,
+    /// The IAM roles of the groups of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub iam_roles_to_override: Vec<String>,
+    /// The preferred IAM role of the user.
+    pub preferred_role: Option<String>,
+}
+
+/// The changes to make to the tokens about to be issued.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreTokenGenerationResponse {
+    /// The claims to change, if any.
+    pub claims_override_details: Option<ClaimsOverrideDetails>,
+}
+
+/// The claims to add to, change in or remove from the ID token.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimsOverrideDetails {
+    /// The claims to add or change.
+    #[serde(default, deserialize_with = "nullable")]
+    pub claims_to_add_or_override: HashMap<String, String>,
+    /// The claims to remove.
+    #[serde(default, deserialize_with = "nullable")]
+    pub claims_to_suppress: Vec<String>,
+    /// The groups and roles to put in the tokens instead of those of the user.
+    pub group_override_details: Option<GroupConfiguration>,
+}
+
+/// An event sent before a message is sent to a user.
+pub type CustomMessageEvent = CognitoTrigger<CustomMessageRequest, CustomMessageResponse>;
+
+/// The details of a message about to be sent.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomMessageRequest {
+    /// The attributes of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub user_attributes: HashMap<String, String>,
+    /// The placeholder to include in the message where the code should appear, `{####}`.
+    pub code_parameter: String,
+    /// The placeholder to include in the message where the user name should appear, for
+    /// messages sent to users created by an administrator.
+    pub username_parameter: Option<String>,
+    /// The client metadata passed to the API.
+    #[serde(default, deserialize_with = "nullable")]
+    pub client_metadata: HashMap<String, String>,
+}
+
+/// The message to send. Messages that are not set are sent as configured in the user pool.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomMessageResponse {
+    /// The text of the SMS message.
+    pub sms_message: Option<String>,
+    /// The body of the email message.
+    pub email_message: Option<String>,
+    /// The subject of the email message.
+    pub email_subject: Option<String>,
+}
+
+/// An event sent when an unknown user signs in or resets their password, to migrate the user
+/// from another directory.
+pub type UserMigrationEvent = CognitoTrigger<UserMigrationRequest, UserMigrationResponse>;
+
+/// The details of the user to migrate.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UserMigrationRequest {
+    /// The password entered by the user, when signing in.
+    pub password: Option<String>,
+    /// The validation data sent by the client.
+    #[serde(default, deserialize_with = "nullable")]
+    pub validation_data: HashMap<String, String>,
+    /// The client metadata passed to the API.
+    #[serde(default, deserialize_with = "nullable")]
+    pub client_metadata: HashMap<String, String>,
+}
+
+/// The user to create in the user pool.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UserMigrationResponse {
+    /// The attributes of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub user_attributes: HashMap<String, String>,
+    /// The status of the user once migrated, such as `CONFIRMED` or `RESET_REQUIRED`.
+    pub final_user_status: Option<String>,
+    /// `SUPPRESS` to not send the welcome message.
+    pub message_action: Option<String>,
+    /// How to send the welcome message, `EMAIL` or `SMS`.
+    #[serde(default, deserialize_with = "nullable")]
+    pub desired_delivery_mediums: Vec<String>,
+    /// Whether to move aliases such as the email address from an existing user.
+    pub force_alias_creation: Option<bool>,
+}
+
+/// An event sent to choose the next challenge of a custom authentication flow.
+pub type DefineAuthChallengeEvent =
+    CognitoTrigger<DefineAuthChallengeRequest, DefineAuthChallengeResponse>;
+
+/// The challenges answered so far in a custom authentication flow.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DefineAuthChallengeRequest {
+    /// The attributes of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub user_attributes: HashMap<String, String>,
+    /// The challenges answered so far, in order.
+    #[serde(default, deserialize_with = "nullable")]
+    pub session: Vec<ChallengeResult>,
+    /// The client metadata passed to the API.
+    #[serde(default, deserialize_with = "nullable")]
+    pub client_metadata: HashMap<String, String>,
+    /// Whether the user does not exist, if the user pool reveals it.
+    #[serde(default)]
+    pub user_not_found: bool,
+}
+
+/// A challenge answered during a custom authentication flow.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChallengeResult {
+    /// The name of the challenge, such as `PASSWORD_VERIFIER` or `CUSTOM_CHALLENGE`.
+    pub challenge_name: String,
+    /// Whether the challenge was answered correctly.
+    pub challenge_result: bool,
+    /// The metadata of a custom challenge.
+    pub challenge_metadata: Option<String>,
+}
+
+/// The next step of a custom authentication flow.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DefineAuthChallengeResponse {
+    /// The name of the next challenge.
+    pub challenge_name: Option<String>,
+    /// Whether the user is authenticated and tokens should be issued.
+    #[serde(default, deserialize_with = "nullable")]
+    pub issue_tokens: bool,
+    /// Whether to end the flow without authenticating the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub fail_authentication: bool,
+}
+
+/// An event sent to create a challenge of a custom authentication flow.
+pub type CreateAuthChallengeEvent =
+    CognitoTrigger<CreateAuthChallengeRequest, CreateAuthChallengeResponse>;
+
+/// The challenge to create.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAuthChallengeRequest {
+    /// The attributes of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub user_attributes: HashMap<String, String>,
+    /// The name of the challenge to create.
+    pub challenge_name: String,
+    /// The challenges answered so far, in order.
+    #[serde(default, deserialize_with = "nullable")]
+    pub session: Vec<ChallengeResult>,
+    /// The client metadata passed to the API.
+    #[serde(default, deserialize_with = "nullable")]
+    pub client_metadata: HashMap<String, String>,
+    /// Whether the user does not exist, if the user pool reveals it.
+    #[serde(default)]
+    pub user_not_found: bool,
+}
+
+/// A challenge of a custom authentication flow.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAuthChallengeResponse {
+    /// The parameters sent to the client, such as the question to answer.
+    #[serde(default, deserialize_with = "nullable")]
+    pub public_challenge_parameters: HashMap<String, String>,
+    /// The parameters kept to verify the answer, such as the expected answer.
+    #[serde(default, deserialize_with = "nullable")]
+    pub private_challenge_parameters: HashMap<String, String>,
+    /// The metadata of the challenge, available to later challenges.
+    pub challenge_metadata: Option<String>,
+}
+
+/// An event sent to verify the answer to a challenge of a custom authentication flow.
+pub type VerifyAuthChallengeResponseEvent =
+    CognitoTrigger<VerifyAuthChallengeResponseRequest, VerifyAuthChallengeResponseResponse>;
+
+/// The answer to verify.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyAuthChallengeResponseRequest {
+    /// The attributes of the user.
+    #[serde(default, deserialize_with = "nullable")]
+    pub user_attributes: HashMap<String, String>,
+    /// The private parameters of the challenge.
+    #[serde(default, deserialize_with = "nullable")]
+    pub private_challenge_parameters: HashMap<String, String>,
+    /// The answer of the user.
+    pub challenge_answer: String,
+    /// The client metadata passed to the API.
+    #[serde(default, deserialize_with = "nullable")]
+    pub client_metadata: HashMap<String, String>,
+    /// Whether the user does not exist, if the user pool reveals it.
+    #[serde(default)]
+    pub user_not_found: bool,
+}
+
+/// Whether the answer to a challenge is correct.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyAuthChallengeResponseResponse {
+    /// Whether the answer is correct.
+    #[serde(default, deserialize_with = "nullable")]
+    pub answer_correct: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CognitoEvent, PreSignUpEvent, PreTokenGenerationEvent};
+    use crate::{handler_fn, testing, Err, LambdaCtx};
+    use serde_json::json;
+
+    async fn auto_confirm(
+        mut event: PreSignUpEvent,
+        _ctx: Option<LambdaCtx>,
+    ) -> Result<PreSignUpEvent, Err> {
+        event.response.auto_confirm_user = true;
+        Ok(event)
+    }
+
+    #[runtime::test]
+    async fn round_trip() {
+        let event = include_str!("../../tests/events/cognito-pre-sign-up-event.json");
+        let ctx = testing::CtxBuilder::new().build();
+        let res = testing::invoke(handler_fn(auto_confirm), event.as_bytes(), ctx).await;
+        let res: serde_json::Value = serde_json::from_slice(&res.unwrap()).unwrap();
+
+        let mut expected: serde_json::Value = serde_json::from_str(event).unwrap();
+        expected["response"]["autoConfirmUser"] = json!(true);
+        assert_eq!(expected, res);
+    }
+
+    #[test]
+    fn dispatch_on_trigger_source() {
+        let event = include_str!("../../tests/events/cognito-pre-sign-up-event.json");
+        match serde_json::from_str(event).unwrap() {
+            CognitoEvent::PreSignUp(event) => {
+                assert_eq!("PreSignUp_SignUp", event.trigger_source);
+                assert_eq!("user@example.com", event.request.user_attributes["email"]);
+                assert!(!event.response.auto_verify_email);
+            }
+            event => panic!("Expected a pre sign-up event, got {:?}", event),
+        }
+
+        let event = json!({
+            "version": "1",
+            "triggerSource": "TokenGeneration_RefreshTokens",
+            "region": "us-east-1",
+            "userPoolId": "us-east-1_example",
+            "userName": "user",
+            "callerContext": {"awsSdkVersion": "aws-sdk-unknown-unknown", "clientId": "client"},
+            "request": {
+                "userAttributes": {"sub": "1234"},
+                "groupConfiguration": {
+                    "groupsToOverride": ["admins"],
+                    "iamRolesToOverride": [],
+                    "preferredRole": null
+                }
+            },
+            "response": {"claimsOverrideDetails": null}
+        });
+        let event: CognitoEvent = serde_json::from_value(event).unwrap();
+        let mut event = match event {
+            CognitoEvent::PreTokenGeneration(event) => event,
+            event => panic!("Expected a pre token generation event, got {:?}", event),
+        };
+        assert_eq!(
+            vec!["admins"],
+            event.request.group_configuration.groups_to_override
+        );
+        assert_eq!(None, event.response.claims_override_details);
+
+        let mut details = super::ClaimsOverrideDetails::default();
+        details.claims_to_suppress.push("email".to_string());
+        event.response.claims_override_details = Some(details);
+        let json = serde_json::to_value(CognitoEvent::PreTokenGeneration(event.clone())).unwrap();
+        assert_eq!(
+            json!(["email"]),
+            json["response"]["claimsOverrideDetails"]["claimsToSuppress"]
+        );
+        let parsed: PreTokenGenerationEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(event, parsed);
+    }
+
+    #[test]
+    fn unknown_trigger_source() {
+        let event = json!({"triggerSource": "Unknown_Trigger", "request": {}});
+        let err = serde_json::from_value::<CognitoEvent>(event).unwrap_err();
+        assert!(err.to_string().contains("Unknown_Trigger"));
+        assert!(serde_json::from_value::<CognitoEvent>(json!({})).is_err());
+    }
+}
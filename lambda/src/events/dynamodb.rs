@@ -0,0 +1,144 @@
+use super::Base64Data;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A batch of records read from a [DynamoDB
+/// stream](https://docs.aws.amazon.com/lambda/latest/dg/with-ddb.html).
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct DynamoDbEvent {
+    /// The records in the batch.
+    #[serde(rename = "Records")]
+    pub records: Vec<DynamoDbRecord>,
+}
+
+/// A change to an item of a DynamoDB table.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamoDbRecord {
+    /// The ID of the record.
+    #[serde(rename = "eventID")]
+    pub event_id: String,
+    /// The kind of change.
+    pub event_name: OperationType,
+    /// The version of the record format.
+    pub event_version: String,
+    /// The source of the event, `aws:dynamodb`.
+    pub event_source: String,
+    /// The region of the table.
+    pub aws_region: String,
+    /// The ARN of the stream.
+    #[serde(rename = "eventSourceARN")]
+    pub event_source_arn: String,
+    /// The change to the item.
+    pub dynamodb: StreamRecord,
+    /// The identity that made the change, for items deleted by Time to Live.
+    pub user_identity: Option<serde_json::Value>,
+}
+
+/// A kind of change to an item of a DynamoDB table.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OperationType {
+    /// An item was added.
+    Insert,
+    /// An item was updated.
+    Modify,
+    /// An item was deleted.
+    Remove,
+}
+
+/// The change to an item described by a DynamoDB stream record.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct StreamRecord {
+    /// When the change happened, in Unix time seconds.
+    pub approximate_creation_date_time: Option<f64>,
+    /// The primary key of the item.
+    #[serde(default)]
+    pub keys: HashMap<String, AttributeValue>,
+    /// The item after the change, if the stream includes new images.
+    #[serde(default)]
+    pub new_image: HashMap<String, AttributeValue>,
+    /// The item before the change, if the stream includes old images.
+    #[serde(default)]
+    pub old_image: HashMap<String, AttributeValue>,
+    /// The sequence number of the record in the stream.
+    pub sequence_number: String,
+    /// The size of the record in bytes.
+    pub size_bytes: u64,
+    /// What the stream includes about changed items, such as `NEW_AND_OLD_IMAGES`.
+    pub stream_view_type: Option<String>,
+}
+
+/// The value of an attribute of a DynamoDB item.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum AttributeValue {
+    /// A string.
+    #[serde(rename = "S")]
+    String(String),
+    /// A number, as the string DynamoDB sends to preserve its precision.
+    #[serde(rename = "N")]
+    Number(String),
+    /// Binary data.
+    #[serde(rename = "B")]
+    Binary(Base64Data),
+    /// A set of strings.
+    #[serde(rename = "SS")]
+    StringSet(Vec<String>),
+    /// A set of numbers.
+    #[serde(rename = "NS")]
+    NumberSet(Vec<String>),
+    /// A set of binary values.
+    #[serde(rename = "BS")]
+    BinarySet(Vec<Base64Data>),
+    /// A map of attributes.
+    #[serde(rename = "M")]
+    Map(HashMap<String, Self>),
+    /// A list of attributes.
+    #[serde(rename = "L")]
+    List(Vec<Self>),
+    /// The absence of a value.
+    #[serde(rename = "NULL")]
+    Null(bool),
+    /// A boolean.
+    #[serde(rename = "BOOL")]
+    Bool(bool),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AttributeValue, DynamoDbEvent, OperationType};
+
+    #[test]
+    fn deserialize_event() {
+        let event = include_str!("../../tests/events/dynamodb-event.json");
+        let event: DynamoDbEvent = serde_json::from_str(event).unwrap();
+        assert_eq!(2, event.records.len());
+
+        let record = &event.records[0];
+        assert_eq!(OperationType::Insert, record.event_name);
+        let change = &record.dynamodb;
+        assert_eq!(Some(1_479_499_740.0), change.approximate_creation_date_time);
+        assert_eq!(AttributeValue::Number("101".into()), change.keys["Id"]);
+        assert_eq!(
+            AttributeValue::String("New item!".into()),
+            change.new_image["Message"]
+        );
+        assert_eq!(
+            AttributeValue::List(vec![
+                AttributeValue::Bool(true),
+                AttributeValue::Binary(super::Base64Data(b"Hello".to_vec())),
+            ]),
+            change.new_image["Flags"]
+        );
+        assert!(change.old_image.is_empty());
+
+        let record = &event.records[1];
+        assert_eq!(OperationType::Remove, record.event_name);
+        assert_eq!(
+            AttributeValue::StringSet(vec!["a".into(), "b".into()]),
+            record.dynamodb.old_image["Tags"]
+        );
+        assert!(record.dynamodb.new_image.is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+/// An event delivered by an [EventBridge
+/// rule](https://docs.aws.amazon.com/lambda/latest/dg/services-cloudwatchevents.html).
+///
+/// The shape of `detail` depends on the source and type of the event, and can be any type
+/// that implements [`Deserialize`].
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct EventBridgeEvent<Detail = serde_json::Value> {
+    /// The version of the event format.
+    pub version: String,
+    /// The ID of the event.
+    pub id: String,
+    /// The type of the event, such as `Scheduled Event`.
+    pub detail_type: String,
+    /// The service or application that emitted the event, such as `aws.events`.
+    pub source: String,
+    /// The ID of the AWS account the event was emitted in.
+    pub account: String,
+    /// When the event was emitted, in RFC 3339 format.
+    pub time: String,
+    /// The region the event was emitted in.
+    pub region: String,
+    /// The ARNs of the resources the event is about.
+    #[serde(default)]
+    pub resources: Vec<String>,
+    /// The details of the event.
+    pub detail: Detail,
+}
+
+/// An event sent by a [schedule
+/// rule](https://docs.aws.amazon.com/eventbridge/latest/userguide/eb-create-rule-schedule.html),
+/// formerly known as a CloudWatch scheduled event.
+///
+/// Its `detail` is always empty, and its only resource is the ARN of the rule.
+pub type ScheduledEvent = EventBridgeEvent<serde_json::Map<String, serde_json::Value>>;
+
+#[cfg(test)]
+mod tests {
+    use super::{EventBridgeEvent, ScheduledEvent};
+    use serde::Deserialize;
+
+    #[test]
+    fn deserialize_scheduled_event() {
+        let event = include_str!("../../tests/events/scheduled-event.json");
+        let event: ScheduledEvent = serde_json::from_str(event).unwrap();
+        assert_eq!("Scheduled Event", event.detail_type);
+        assert_eq!("aws.events", event.source);
+        assert_eq!("2015-10-08T16:53:06Z", event.time);
+        assert_eq!(
+            vec!["arn:aws:events:us-east-1:123456789012:rule/my-schedule".to_string()],
+            event.resources
+        );
+        assert!(event.detail.is_empty());
+    }
+
+    #[test]
+    fn deserialize_typed_detail() {
+        #[derive(Deserialize, Debug, PartialEq, Eq)]
+        struct StateChange {
+            instance: String,
+            state: String,
+        }
+
+        let event = r#"{
+            "version": "0",
+            "id": "7bf73129-1428-4cd3-a780-95db273d1602",
+            "detail-type": "EC2 Instance State-change Notification",
+            "source": "aws.ec2",
+            "account": "123456789012",
+            "time": "2015-11-11T21:29:54Z",
+            "region": "us-east-1",
+            "resources": [],
+            "detail": {"instance": "i-abcd1111", "state": "pending"}
+        }"#;
+        let event: EventBridgeEvent<StateChange> = serde_json::from_str(event).unwrap();
+        assert_eq!("aws.ec2", event.source);
+        assert_eq!("pending", event.detail.state);
+        assert_eq!("i-abcd1111", event.detail.instance);
+    }
+}
@@ -0,0 +1,77 @@
+use super::Base64Data;
+use serde::{Deserialize, Serialize};
+
+/// A batch of records read from a [Kinesis data
+/// stream](https://docs.aws.amazon.com/lambda/latest/dg/with-kinesis.html).
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct KinesisEvent {
+    /// The records in the batch.
+    #[serde(rename = "Records")]
+    pub records: Vec<KinesisEventRecord>,
+}
+
+/// A record read from a Kinesis data stream.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KinesisEventRecord {
+    /// The ID of the record, made of its shard and sequence number.
+    #[serde(rename = "eventID")]
+    pub event_id: String,
+    /// The name of the event, `aws:kinesis:record`.
+    pub event_name: String,
+    /// The version of the record format.
+    pub event_version: String,
+    /// The source of the event, `aws:kinesis`.
+    pub event_source: String,
+    /// The ARN of the stream.
+    #[serde(rename = "eventSourceARN")]
+    pub event_source_arn: String,
+    /// The region of the stream.
+    pub aws_region: String,
+    /// The ARN of the role used to read the stream.
+    pub invoke_identity_arn: String,
+    /// The data record.
+    pub kinesis: KinesisRecord,
+}
+
+/// A data record of a Kinesis data stream.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KinesisRecord {
+    /// When the record was added to the stream, in Unix time seconds.
+    pub approximate_arrival_timestamp: f64,
+    /// The data put into the stream.
+    pub data: Base64Data,
+    /// The key that determined the shard of the record.
+    pub partition_key: String,
+    /// The sequence number of the record in its shard.
+    pub sequence_number: String,
+    /// The version of the schema of the record.
+    pub kinesis_schema_version: String,
+    /// How the record is encrypted at rest, such as `KMS`, if it is.
+    pub encryption_type: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KinesisEvent;
+
+    #[test]
+    fn deserialize_event() {
+        let event = include_str!("../../tests/events/kinesis-event.json");
+        let event: KinesisEvent = serde_json::from_str(event).unwrap();
+        let record = &event.records[0];
+        assert_eq!("aws:kinesis:record", record.event_name);
+        assert_eq!(
+            "arn:aws:kinesis:us-east-1:123456789012:stream/lambda-stream",
+            record.event_source_arn
+        );
+        assert_eq!(b"Hello, this is a test.", record.kinesis.data.as_ref());
+        assert_eq!("1", record.kinesis.partition_key);
+        assert_eq!(
+            1_545_084_650.987,
+            record.kinesis.approximate_arrival_timestamp
+        );
+        assert_eq!(None, record.kinesis.encryption_type);
+    }
+}
@@ -0,0 +1,55 @@
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Log events sent by CloudWatch Logs subscription filters.
+pub mod cloudwatch_logs;
+/// Events sent by Cognito user pool triggers.
+pub mod cognito;
+/// Events sent by DynamoDB Streams.
+pub mod dynamodb;
+/// Events sent by Amazon EventBridge, including CloudWatch scheduled events.
+pub mod eventbridge;
+/// Events sent by Kinesis Data Streams.
+pub mod kinesis;
+/// Event notifications sent by Amazon S3.
+pub mod s3;
+/// Notifications sent by Amazon SNS.
+pub mod sns;
+/// Batches of messages sent by Amazon SQS.
+pub mod sqs;
+
+/// Binary data, encoded as base64 in the payload of an event.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Base64Data(pub Vec<u8>);
+
+impl<'de> Deserialize<'de> for Base64Data {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let data = base64::decode(&encoded).map_err(D::Error::custom)?;
+        Ok(Self(data))
+    }
+}
+
+impl Serialize for Base64Data {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::encode(&self.0))
+    }
+}
+
+impl AsRef<[u8]> for Base64Data {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Base64Data;
+
+    #[test]
+    fn base64_data() {
+        let data: Base64Data = serde_json::from_str(r#""SGVsbG8=""#).unwrap();
+        assert_eq!(b"Hello", data.as_ref());
+        assert_eq!(r#""SGVsbG8=""#, serde_json::to_string(&data).unwrap());
+        assert!(serde_json::from_str::<Base64Data>(r#""not base64!""#).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Event notifications received from an [S3
+/// bucket](https://docs.aws.amazon.com/lambda/latest/dg/with-s3.html).
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct S3Event {
+    /// The notifications in the event.
+    #[serde(rename = "Records")]
+    pub records: Vec<S3EventRecord>,
+}
+
+/// A notification of a change to an object in an S3 bucket.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct S3EventRecord {
+    /// The version of the event format.
+    pub event_version: String,
+    /// The source of the event, `aws:s3`.
+    pub event_source: String,
+    /// The region of the bucket.
+    pub aws_region: String,
+    /// When the change happened, in RFC 3339 format.
+    pub event_time: String,
+    /// The kind of change, such as `ObjectCreated:Put`.
+    pub event_name: String,
+    /// The identity that made the change.
+    pub user_identity: S3UserIdentity,
+    /// The parameters of the request that made the change.
+    pub request_parameters: S3RequestParameters,
+    /// Elements of the response to the request that made the change, such as
+    /// `x-amz-request-id`.
+    #[serde(default)]
+    pub response_elements: HashMap<String, String>,
+    /// The bucket and object that changed.
+    pub s3: S3Entity,
+}
+
+/// The identity of an AWS principal.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct S3UserIdentity {
+    /// The ID of the principal.
+    pub principal_id: String,
+}
+
+/// The parameters of a request made to S3.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct S3RequestParameters {
+    /// The IP address the request was made from.
+    #[serde(rename = "sourceIPAddress")]
+    pub source_ip_address: String,
+}
+
+/// The bucket and object that an S3 notification is about.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct S3Entity {
+    /// The version of the schema of this entity.
+    #[serde(rename = "s3SchemaVersion")]
+    pub schema_version: String,
+    /// The ID of the notification configuration that matched the change.
+    pub configuration_id: String,
+    /// The bucket the object is stored in.
+    pub bucket: S3Bucket,
+    /// The object that changed.
+    pub object: S3Object,
+}
+
+/// An S3 bucket.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct S3Bucket {
+    /// The name of the bucket.
+    pub name: String,
+    /// The owner of the bucket.
+    pub owner_identity: S3UserIdentity,
+    /// The ARN of the bucket.
+    pub arn: String,
+}
+
+/// An object stored in an S3 bucket.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct S3Object {
+    /// The key of the object. Notifications encode keys as in a URL query string, with spaces
+    /// replaced by `+`.
+    pub key: String,
+    /// The size of the object in bytes. Absent for deletions.
+    pub size: Option<u64>,
+    /// The entity tag of the object.
+    pub e_tag: Option<String>,
+    /// The version of the object, if the bucket is versioned.
+    pub version_id: Option<String>,
+    /// A value that orders the notifications about the same object.
+    pub sequencer: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::S3Event;
+
+    #[test]
+    fn deserialize_event() {
+        let event = include_str!("../../tests/events/s3-event.json");
+        let event: S3Event = serde_json::from_str(event).unwrap();
+        let record = &event.records[0];
+        assert_eq!("ObjectCreated:Put", record.event_name);
+        assert_eq!("127.0.0.1", record.request_parameters.source_ip_address);
+        assert_eq!(
+            "C3D13FE58DE4C810",
+            record.response_elements["x-amz-request-id"]
+        );
+        assert_eq!("example-bucket", record.s3.bucket.name);
+        assert_eq!("test%2Fkey", record.s3.object.key);
+        assert_eq!(Some(1024), record.s3.object.size);
+        assert_eq!(None, record.s3.object.version_id);
+    }
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Notifications received from an [SNS
+/// topic](https://docs.aws.amazon.com/lambda/latest/dg/with-sns.html).
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnsEvent {
+    /// The notifications, of which there is always one.
+    #[serde(rename = "Records")]
+    pub records: Vec<SnsRecord>,
+}
+
+/// A notification received from an SNS topic.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct SnsRecord {
+    /// The source of the event, `aws:sns`.
+    pub event_source: String,
+    /// The version of the event format.
+    pub event_version: String,
+    /// The ARN of the subscription that delivered the notification.
+    pub event_subscription_arn: String,
+    /// The message published to the topic.
+    pub sns: SnsMessage,
+}
+
+/// A message published to an SNS topic.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct SnsMessage {
+    /// The type of the message, `Notification`.
+    #[serde(rename = "Type")]
+    pub kind: String,
+    /// The ID of the message.
+    pub message_id: String,
+    /// The ARN of the topic the message was published to.
+    pub topic_arn: String,
+    /// The subject of the message, if any.
+    pub subject: Option<String>,
+    /// The body of the message.
+    pub message: String,
+    /// When the message was published, in RFC 3339 format.
+    pub timestamp: String,
+    /// The version of the signature.
+    pub signature_version: String,
+    /// The signature of the message.
+    pub signature: String,
+    /// The URL of the certificate used to sign the message.
+    #[serde(rename = "SigningCertUrl", alias = "SigningCertURL")]
+    pub signing_cert_url: String,
+    /// The URL to unsubscribe from the topic.
+    #[serde(rename = "UnsubscribeUrl", alias = "UnsubscribeURL")]
+    pub unsubscribe_url: String,
+    /// The attributes set by the publisher of the message.
+    #[serde(default)]
+    pub message_attributes: HashMap<String, SnsMessageAttribute>,
+}
+
+/// An attribute set by the publisher of an SNS message.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct SnsMessageAttribute {
+    /// The type of the attribute, such as `String` or `Binary`.
+    #[serde(rename = "Type")]
+    pub kind: String,
+    /// The value of the attribute. Binary values are encoded as base64.
+    pub value: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnsEvent;
+
+    #[test]
+    fn deserialize_event() {
+        let event = include_str!("../../tests/events/sns-event.json");
+        let event: SnsEvent = serde_json::from_str(event).unwrap();
+        assert_eq!(1, event.records.len());
+
+        let message = &event.records[0].sns;
+        assert_eq!("Notification", message.kind);
+        assert_eq!(
+            "arn:aws:sns:us-east-1:123456789012:sns-lambda",
+            message.topic_arn
+        );
+        assert_eq!(
+            Some("TestInvoke"),
+            message.subject.as_ref().map(String::as_str)
+        );
+        assert_eq!("Hello from SNS!", message.message);
+        assert_eq!("2019-01-02T12:45:07.000Z", message.timestamp);
+        assert_eq!("Number", message.message_attributes["Test"].kind);
+        assert_eq!("42", message.message_attributes["Test"].value);
+    }
+}
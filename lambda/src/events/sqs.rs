@@ -0,0 +1,91 @@
+use super::Base64Data;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A batch of messages received from an [SQS
+/// queue](https://docs.aws.amazon.com/lambda/latest/dg/with-sqs.html).
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SqsEvent {
+    /// The messages in the batch.
+    #[serde(rename = "Records")]
+    pub records: Vec<SqsMessage>,
+}
+
+/// A message received from an SQS queue.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SqsMessage {
+    /// The ID of the message.
+    pub message_id: String,
+    /// The handle used to delete the message from the queue.
+    pub receipt_handle: String,
+    /// The body of the message.
+    pub body: String,
+    /// The MD5 digest of the body.
+    pub md5_of_body: String,
+    /// System attributes of the message, such as `ApproximateReceiveCount` and
+    /// `MessageGroupId`.
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+    /// The attributes set by the sender of the message.
+    #[serde(default)]
+    pub message_attributes: HashMap<String, SqsMessageAttribute>,
+    /// The source of the event, `aws:sqs`.
+    pub event_source: String,
+    /// The ARN of the queue.
+    #[serde(rename = "eventSourceARN")]
+    pub event_source_arn: String,
+    /// The region of the queue.
+    pub aws_region: String,
+}
+
+/// An attribute set by the sender of an SQS message.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SqsMessageAttribute {
+    /// The type of the attribute, such as `String`, `Number` or `Binary`.
+    pub data_type: String,
+    /// The value of the attribute, unless it is binary.
+    pub string_value: Option<String>,
+    /// The value of a binary attribute.
+    pub binary_value: Option<Base64Data>,
+    /// Reserved for list values.
+    #[serde(default)]
+    pub string_list_values: Vec<String>,
+    /// Reserved for binary list values.
+    #[serde(default)]
+    pub binary_list_values: Vec<Base64Data>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SqsEvent;
+
+    #[test]
+    fn deserialize_event() {
+        let event = include_str!("../../tests/events/sqs-event.json");
+        let event: SqsEvent = serde_json::from_str(event).unwrap();
+        assert_eq!(2, event.records.len());
+
+        let message = &event.records[0];
+        assert_eq!("059f36b4-87a3-44ab-83d2-661975830a7d", message.message_id);
+        assert_eq!("Test message.", message.body);
+        assert_eq!("1", message.attributes["ApproximateReceiveCount"]);
+        assert_eq!(
+            "arn:aws:sqs:us-east-1:123456789012:my-queue",
+            message.event_source_arn
+        );
+        let attribute = &message.message_attributes["Greeting"];
+        assert_eq!("String", attribute.data_type);
+        assert_eq!(
+            Some("Hello"),
+            attribute.string_value.as_ref().map(String::as_str)
+        );
+        let attribute = &message.message_attributes["Checksum"];
+        assert_eq!(
+            Some(&b"\x00\x01"[..]),
+            attribute.binary_value.as_ref().map(AsRef::as_ref)
+        );
+        assert!(event.records[1].message_attributes.is_empty());
+    }
+}
@@ -0,0 +1,363 @@
+use crate::{
+    client::{Client, EventClient, EventStream},
+    err_fmt, error_hook, runtime_api, Err,
+};
+use bytes::Bytes;
+use futures::prelude::*;
+use http::{header::HeaderName, HeaderMap, HeaderValue, Method, Request, Response, Uri};
+use serde::{Deserialize, Serialize};
+
+const NAME: &str = "lambda-extension-name";
+const IDENTIFIER: &str = "lambda-extension-identifier";
+const ERROR_TYPE: &str = "lambda-extension-function-error-type";
+
+/// The lifecycle events an extension can subscribe to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EventType {
+    /// Sent when the function is invoked. Only external extensions may subscribe to it.
+    Invoke,
+    /// Sent when Lambda is about to shut down the execution environment.
+    Shutdown,
+}
+
+/// A lifecycle event received from the [Extensions
+/// API](https://docs.aws.amazon.com/lambda/latest/dg/runtimes-extensions-api.html).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "eventType", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LifecycleEvent {
+    /// The function is being invoked.
+    #[serde(rename_all = "camelCase")]
+    Invoke {
+        /// The deadline of the invocation, in Unix time milliseconds.
+        deadline_ms: u64,
+        /// The AWS request ID of the invocation.
+        request_id: String,
+        /// The ARN of the Lambda function being invoked.
+        invoked_function_arn: String,
+        /// The X-Ray tracing header of the invocation.
+        tracing: Option<Tracing>,
+    },
+    /// The execution environment is shutting down.
+    #[serde(rename_all = "camelCase")]
+    Shutdown {
+        /// Why the execution environment is shutting down.
+        shutdown_reason: ShutdownReason,
+        /// The time by which the extension must exit, in Unix time milliseconds.
+        deadline_ms: u64,
+    },
+}
+
+/// The tracing header of an invocation.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Tracing {
+    /// The kind of tracing header, such as `X-Amzn-Trace-Id`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The value of the tracing header.
+    pub value: String,
+}
+
+/// The reason the execution environment is shutting down.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ShutdownReason {
+    /// The execution environment was idle.
+    Spindown,
+    /// An invocation or the initialization phase timed out.
+    Timeout,
+    /// The function or an extension failed.
+    Failure,
+}
+
+/// Details about the function, returned when an extension registers.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Registration {
+    /// The name of the function.
+    pub function_name: String,
+    /// The version of the function.
+    pub function_version: String,
+    /// The handler configured for the function.
+    pub handler: String,
+}
+
+#[derive(Serialize)]
+struct RegisterRequest<'a> {
+    events: &'a [EventType],
+}
+
+/// An extension registered with the [Extensions
+/// API](https://docs.aws.amazon.com/lambda/latest/dg/runtimes-extensions-api.html).
+///
+/// # Example
+/// ```no_run
+/// #![feature(async_await)]
+///
+/// use futures::prelude::*;
+/// use lambda::extension::{EventType, Extension, LifecycleEvent};
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// #[runtime::main]
+/// async fn main() -> Result<(), Err> {
+///     let extension = Extension::register("cache-warmer", &[EventType::Shutdown]).await?;
+///     let events = extension.events();
+///     futures::pin_mut!(events);
+///     while let Some(event) = events.next().await {
+///         if let LifecycleEvent::Shutdown { .. } = event? {
+///             break;
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Extension<C> {
+    client: C,
+    id: String,
+    registration: Registration,
+}
+
+impl Extension<Client> {
+    /// Registers an extension named `name` for `events` with the Extensions API at the
+    /// address in `AWS_LAMBDA_RUNTIME_API`.
+    ///
+    /// External extensions must be registered with the file name of their executable.
+    ///
+    /// # Errors
+    /// Returns an error if the Extensions API is unavailable or rejects the registration.
+    pub async fn register(name: &str, events: &[EventType]) -> Result<Self, Err> {
+        let client = Client::new(runtime_api()?);
+        Self::register_with_client(client, name, events).await
+    }
+}
+
+impl<C> Extension<C>
+where
+    C: for<'a> EventClient<'a>,
+{
+    /// Registers an extension named `name` for `events`, using `client` to communicate with
+    /// the Extensions API.
+    ///
+    /// # Errors
+    /// Returns an error if `client` fails to deliver the request or the registration is
+    /// rejected.
+    pub async fn register_with_client(
+        client: C,
+        name: &str,
+        events: &[EventType],
+    ) -> Result<Self, Err> {
+        let body = serde_json::to_vec(&RegisterRequest { events })?;
+        let req = Request::builder()
+            .uri(Uri::from_static("/2020-01-01/extension/register"))
+            .method(Method::POST)
+            .header(NAME, name)
+            .body(Bytes::from(body))?;
+
+        let res = check(client.call(req).await?)?;
+        let id = res
+            .headers()
+            .get(IDENTIFIER)
+            .ok_or_else(|| err_fmt!("Missing {} header", IDENTIFIER))?
+            .to_str()?
+            .to_string();
+        let registration = serde_json::from_slice(res.body())?;
+        Ok(Self {
+            client,
+            id,
+            registration,
+        })
+    }
+
+    /// The identifier assigned to the extension by Lambda.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Details about the function the extension was registered for.
+    pub const fn registration(&self) -> &Registration {
+        &self.registration
+    }
+
+    /// Returns a stream of the lifecycle events the extension registered for.
+    ///
+    /// Lambda freezes the execution environment while the extension waits for the next
+    /// event, so the stream should be polled as soon as the previous event is processed.
+    pub fn events(&self) -> impl Stream<Item = Result<LifecycleEvent, Err>> + '_ {
+        let uri = Uri::from_static("/2020-01-01/extension/event/next");
+        EventStream::with_request(&self.client, uri, self.headers()).map(|res| {
+            let res = check(res?)?;
+            Ok(serde_json::from_slice(res.body())?)
+        })
+    }
+
+    /// Reports an error that occurred while the extension initialized. Lambda then fails
+    /// the initialization of the function.
+    ///
+    /// # Errors
+    /// Returns an error if `client` fails to deliver the report.
+    pub async fn init_error<E: Into<Err>>(&self, err: E) -> Result<(), Err> {
+        self.report("/2020-01-01/extension/init/error", err).await
+    }
+
+    /// Reports an error that is causing the extension to exit.
+    ///
+    /// # Errors
+    /// Returns an error if `client` fails to deliver the report.
+    pub async fn exit_error<E: Into<Err>>(&self, err: E) -> Result<(), Err> {
+        self.report("/2020-01-01/extension/exit/error", err).await
+    }
+
+    async fn report<E: Into<Err>>(&self, path: &'static str, err: E) -> Result<(), Err> {
+        let report = error_hook::report_error(err);
+        // Lambda expects the error type in the form `Category.Reason`.
+        let error_type = format!("Extension.{}", report.name);
+        let body = serde_json::to_vec(&report)?;
+        let req = Request::builder()
+            .uri(Uri::from_static(path))
+            .method(Method::POST)
+            .header(ERROR_TYPE, error_type.as_str())
+            .body(Bytes::from(body))?;
+
+        self.call(req).await?;
+        Ok(())
+    }
+
+    /// Sends `req` on behalf of the extension, failing if it is not accepted.
+    pub(crate) async fn call(&self, mut req: Request<Bytes>) -> Result<Response<Bytes>, Err> {
+        req.headers_mut().extend(self.headers());
+        check(self.client.call(req).await?)
+    }
+
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Ok(id) = HeaderValue::from_str(&self.id) {
+            headers.insert(HeaderName::from_static(IDENTIFIER), id);
+        }
+        headers
+    }
+}
+
+/// Fails if the Extensions API did not accept a request.
+fn check(res: Response<Bytes>) -> Result<Response<Bytes>, Err> {
+    if res.status().is_success() {
+        Ok(res)
+    } else {
+        let body = String::from_utf8_lossy(res.body());
+        Err(err_fmt!("Extensions API returned {}: {}", res.status(), body).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventType, Extension, LifecycleEvent, ShutdownReason};
+    use crate::{client::EventClient, Err};
+    use bytes::Bytes;
+    use futures::{
+        future::{self, Ready},
+        prelude::*,
+    };
+    use http::{Request, Response};
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+    };
+
+    /// An `EventClient` that serves queued lifecycle events and records every request.
+    #[derive(Default, Clone)]
+    struct FakeClient {
+        events: Arc<Mutex<VecDeque<&'static str>>>,
+        requests: Arc<Mutex<Vec<Request<Bytes>>>>,
+    }
+
+    impl<'a> EventClient<'a> for FakeClient {
+        type Fut = Ready<Result<Response<Bytes>, Err>>;
+
+        fn call(&self, req: Request<Bytes>) -> Self::Fut {
+            let res = match req.uri().path() {
+                "/2020-01-01/extension/register" => Response::builder()
+                    .header("lambda-extension-identifier", "ext-id")
+                    .body(Bytes::from(
+                        r#"{"functionName":"test","functionVersion":"$LATEST","handler":"bootstrap"}"#,
+                    ))
+                    .unwrap(),
+                "/2020-01-01/extension/event/next" => {
+                    let event = self.events.lock().unwrap().pop_front();
+                    let res = event.map(|event| Response::new(Bytes::from(event)));
+                    let res = res.ok_or_else(|| crate::err_fmt!("no more events").into());
+                    self.requests.lock().unwrap().push(req);
+                    return future::ready(res);
+                }
+                _ => Response::new(Bytes::new()),
+            };
+            self.requests.lock().unwrap().push(req);
+            future::ready(Ok(res))
+        }
+    }
+
+    #[runtime::test]
+    async fn lifecycle_events() -> Result<(), Err> {
+        let client = FakeClient::default();
+        client.events.lock().unwrap().extend(vec![
+            r#"{"eventType":"INVOKE","deadlineMs":1581512138111,"requestId":"3da1f2dc-3222-475e-9205-e2e6c6318895","invokedFunctionArn":"arn:aws:lambda:us-east-1:123456789012:function:test","tracing":{"type":"X-Amzn-Trace-Id","value":"Root=1-5f35ae12-0c0fec141ab77a00bc047aa2"}}"#,
+            r#"{"eventType":"SHUTDOWN","shutdownReason":"spindown","deadlineMs":1581512138111}"#,
+        ]);
+        let events = [EventType::Invoke, EventType::Shutdown];
+        let extension = Extension::register_with_client(client.clone(), "ext", &events).await?;
+        assert_eq!("ext-id", extension.id());
+        assert_eq!("bootstrap", extension.registration().handler);
+
+        let events: Vec<_> = extension.events().take(2).collect().await;
+        match &events[0] {
+            Ok(LifecycleEvent::Invoke { request_id, .. }) => {
+                assert_eq!("3da1f2dc-3222-475e-9205-e2e6c6318895", request_id)
+            }
+            event => panic!("Expected an INVOKE event, got {:?}", event),
+        }
+        match &events[1] {
+            Ok(LifecycleEvent::Shutdown {
+                shutdown_reason, ..
+            }) => assert_eq!(ShutdownReason::Spindown, *shutdown_reason),
+            event => panic!("Expected a SHUTDOWN event, got {:?}", event),
+        }
+
+        let requests = client.requests.lock().unwrap();
+        assert_eq!(
+            r#"{"events":["INVOKE","SHUTDOWN"]}"#,
+            std::str::from_utf8(requests[0].body())?
+        );
+        assert_eq!("ext", requests[0].headers()["lambda-extension-name"]);
+        assert_eq!(
+            "ext-id",
+            requests[1].headers()["lambda-extension-identifier"]
+        );
+        Ok(())
+    }
+
+    #[runtime::test]
+    async fn report_errors() -> Result<(), Err> {
+        let client = FakeClient::default();
+        let extension = Extension::register_with_client(client.clone(), "ext", &[]).await?;
+        extension.init_error("failed to start").await?;
+        extension.exit_error("failed to flush").await?;
+
+        let requests = client.requests.lock().unwrap();
+        let paths: Vec<_> = requests.iter().map(|req| req.uri().path()).collect();
+        assert_eq!(
+            vec![
+                "/2020-01-01/extension/register",
+                "/2020-01-01/extension/init/error",
+                "/2020-01-01/extension/exit/error",
+            ],
+            paths
+        );
+        assert_eq!(
+            "ext-id",
+            requests[2].headers()["lambda-extension-identifier"]
+        );
+        assert!(requests[2]
+            .headers()
+            .contains_key("lambda-extension-function-error-type"));
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-#![feature(async_await)]
+#![feature(async_await, error_generic_member_access)]
 #![deny(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 #![warn(missing_docs, nonstandard_style, rust_2018_idioms)]
 
@@ -33,23 +33,71 @@
 //!     Ok(event)
 //! }
 //! ```
-pub use crate::types::LambdaCtx;
+pub use crate::types::{
+    ClientApplication, ClientContext, CognitoIdentity, InvalidHeader, LambdaCtx,
+};
 use bytes::Bytes;
 use client::{Client, EventClient, EventStream};
-use futures::prelude::*;
+use codec::{Codec, Json};
+use error_hook::{ErrorReport, ErrorReporter};
+use futures::{
+    future::{self, BoxFuture, Either},
+    prelude::*,
+};
+use headers::HeaderMapExt;
 use http::{Method, Request, Response, Uri};
 pub use lambda_attributes::lambda;
+use pin_utils::pin_mut;
 use serde::{Deserialize, Serialize};
-use std::{convert::TryFrom, env};
+use std::{convert::TryFrom, env, panic::AssertUnwindSafe, sync::Arc, time::Duration};
+use types::RequestId;
 
-mod client;
+/// Handlers of batches of records that report which of the records failed.
+///
+/// Requires the `events` feature.
+#[cfg(feature = "events")]
+pub mod batch;
+/// Clients for the Lambda Runtime APIs.
+pub mod client;
+/// Codecs that decode the events passed to handlers and encode their outputs.
+pub mod codec;
+/// Handlers of CloudFormation custom resources.
+pub mod custom_resource;
+/// A local implementation of the Runtime API for end-to-end tests.
+pub mod emulator;
 /// Mechanism to provide a custom error reporting hook.
 pub mod error_hook;
+/// Typed payloads of events sent by common AWS event sources.
+///
+/// Requires the `events` feature, which is not enabled by default:
+///
+/// ```toml
+/// [dependencies]
+/// lambda = { version = "0.1", features = ["events"] }
+/// ```
+#[cfg(feature = "events")]
+pub mod events;
+/// A client for the Lambda Extensions API.
+pub mod extension;
+/// Adapters for running an [`HttpHandler`] behind HTTP integrations such as API Gateway.
+pub mod proxy;
+/// Listens for the signal sent before the execution environment shuts down.
+mod shutdown;
+/// A subscriber for the Lambda Telemetry API.
+pub mod telemetry;
+/// Helpers for invoking a handler in tests without a Runtime API.
+pub mod testing;
 /// Types availible to a Lambda function.
 mod types;
 
 type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
 
+/// A function called when the execution environment shuts down.
+type ShutdownHook = Arc<dyn Fn() -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// The time allowed for shutdown hooks to complete, unless configured otherwise.
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_millis(500);
+
 #[derive(Debug)]
 /// A string error, which can be display
 pub(crate) struct StringError(pub String);
@@ -102,12 +150,12 @@ impl Config {
     }
 }
 
-/// A trait describing an asynchronous function from `Event` to `Output`. `Event` and `Output` must implement [`Deserialize`](serde::Deserialize) and [`Serialize`](serde::Serialize).
-pub trait Handler<Event, Output>
-where
-    Event: for<'de> Deserialize<'de>,
-    Output: Serialize,
-{
+/// A trait describing an asynchronous function from `Event` to `Output`.
+///
+/// Events are decoded and outputs encoded by the [`Codec`] of the [`Runtime`], so with the
+/// default [`Json`] codec `Event` and `Output` must implement [`Deserialize`](serde::Deserialize)
+/// and [`Serialize`](serde::Serialize).
+pub trait Handler<Event, Output> {
     /// Errors returned by this handler.
     type Err: Into<Err>;
     /// The future response value of this handler.
@@ -120,14 +168,23 @@ where
     fn call(&mut self, event: Event, ctx: Option<LambdaCtx>) -> Self::Fut;
 }
 
-/// A trait describing an asynchronous function from `Request<A>` to `Response<B>`. `A` and `B` must implement [`Deserialize`](serde::Deserialize) and [`Serialize`](serde::Serialize).
-pub trait HttpHandler<A, B>: Handler<Request<A>, Response<B>>
+/// A trait describing an asynchronous function from an HTTP `Request` to a `Response`, for
+/// functions invoked through an HTTP integration such as API Gateway.
+///
+/// The body of the response can be any type that converts into [`Bytes`], such as a
+/// `String` or a `Vec<u8>`. Use [`proxy::adapter`] or [`run_http`] to run an `HttpHandler`.
+pub trait HttpHandler<Body>
 where
-    Request<A>: for<'de> Deserialize<'de>,
-    Response<B>: Serialize,
+    Body: Into<Bytes>,
 {
+    /// Errors returned by this handler.
+    type Err: Into<Err>;
+    /// The future response value of this handler.
+    type Fut: Future<Output = Result<Response<Body>, Self::Err>>;
     /// Process the incoming request and return the response asynchronously.
-    fn call_http(&mut self, event: Request<A>) -> <Self as Handler<Request<A>, Response<B>>>::Fut;
+    ///
+    /// The [`LambdaCtx`] of the invocation is available in the extensions of `req`.
+    fn call_http(&mut self, req: Request<Bytes>) -> Self::Fut;
 }
 
 /// Returns a new `HandlerFn` with the given closure.
@@ -144,8 +201,6 @@ pub struct HandlerFn<Function> {
 impl<Function, Event, Output, Error, Fut> Handler<Event, Output> for HandlerFn<Function>
 where
     Function: Fn(Event, Option<LambdaCtx>) -> Fut,
-    Event: for<'de> Deserialize<'de>,
-    Output: Serialize,
     Error: Into<Err>,
     Fut: Future<Output = Result<Output, Error>> + Send,
 {
@@ -157,6 +212,21 @@ where
     }
 }
 
+impl<Function, Body, Error, Fut> HttpHandler<Body> for HandlerFn<Function>
+where
+    Function: Fn(Request<Bytes>, Option<LambdaCtx>) -> Fut,
+    Body: Into<Bytes>,
+    Error: Into<Err>,
+    Fut: Future<Output = Result<Response<Body>, Error>> + Send,
+{
+    type Err = Error;
+    type Fut = Fut;
+    fn call_http(&mut self, req: Request<Bytes>) -> Self::Fut {
+        let ctx = req.extensions().get::<LambdaCtx>().cloned();
+        (self.f)(req, ctx)
+    }
+}
+
 /// Starts the Lambda Rust runtime and begins polling for events on the [Lambda
 /// Runtime APIs](https://docs.aws.amazon.com/lambda/latest/dg/runtimes-api.html).
 ///
@@ -182,49 +252,446 @@ where
 /// }
 /// ```
 pub async fn run<Function, Event, Output>(
-    mut handler: Function,
+    handler: Function,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>
 where
     Function: Handler<Event, Output>,
     Event: for<'de> Deserialize<'de>,
     Output: Serialize,
 {
-    let uri: Bytes = env::var("AWS_LAMBDA_RUNTIME_API")?.into();
-    let uri = Uri::from_shared(uri)?;
-    let client = Client::new(uri);
-    let mut stream = EventStream::new(&client);
-
-    while let Some(event) = stream.next().await {
-        let (parts, body) = event?.into_parts();
-        let mut ctx: LambdaCtx = LambdaCtx::try_from(parts.headers)?;
-        ctx.env_config = Config::from_env()?;
-        let body = serde_json::from_slice(&body)?;
-
-        match handler.call(body, Some(ctx.clone())).await {
-            Ok(res) => {
-                let res = serde_json::to_vec(&res)?;
-                let uri = format!("/runtime/invocation/{}/response", &ctx.id).parse::<Uri>()?;
-                let req = Request::builder()
-                    .uri(uri)
-                    .method(Method::POST)
-                    .body(Bytes::from(res))?;
-
-                client.call(req).await?;
+    Runtime::new().run(handler).await
+}
+
+/// Starts the Lambda Rust runtime with an [`HttpHandler`], which is invoked with the HTTP
+/// requests received from an integration such as API Gateway.
+///
+/// See [`proxy::adapter`].
+///
+/// # Errors
+/// Returns an error if the runtime fails to initialize or loses its connection to the Runtime APIs.
+pub async fn run_http<Function, Body>(handler: Function) -> Result<(), Err>
+where
+    Function: HttpHandler<Body>,
+    Function::Fut: Send + 'static,
+    Body: Into<Bytes>,
+{
+    run(proxy::adapter(handler)).await
+}
+
+/// A configurable Lambda runtime. [`run`] starts a runtime with the default settings,
+/// which is sufficient for most functions.
+///
+/// `Format` is the [`Codec`] of the handler run by the runtime, [`Json`] unless set with
+/// [`codec`](Runtime::codec).
+///
+/// # Example
+/// ```no_run
+/// #![feature(async_await)]
+///
+/// use lambda::{handler_fn, LambdaCtx, Runtime};
+/// use std::time::Duration;
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// #[runtime::main]
+/// async fn main() -> Result<(), Err> {
+///     let func = handler_fn(func);
+///     Runtime::new()
+///         .enforce_deadline(Duration::from_millis(100))
+///         .run(func)
+///         .await?;
+///     Ok(())
+/// }
+///
+/// async fn func(event: String, _ctx: Option<LambdaCtx>) -> Result<String, Err> {
+///     Ok(event)
+/// }
+/// ```
+#[derive(Default, Clone)]
+pub struct Runtime<Format = Json> {
+    codec: Format,
+    deadline_margin: Option<Duration>,
+    reporter: Option<Arc<dyn ErrorReporter>>,
+    shutdown_hooks: Vec<ShutdownHook>,
+    shutdown_timeout: Option<Duration>,
+}
+
+impl<Format: std::fmt::Debug> std::fmt::Debug for Runtime<Format> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Runtime")
+            .field("codec", &self.codec)
+            .field("deadline_margin", &self.deadline_margin)
+            .field("shutdown_hooks", &self.shutdown_hooks.len())
+            .field("shutdown_timeout", &self.shutdown_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Runtime {
+    /// Returns a runtime with the default settings.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<Format> Runtime<Format> {
+    /// Decodes events and encodes outputs using `codec` instead of [`Json`].
+    #[must_use]
+    pub fn codec<C>(self, codec: C) -> Runtime<C> {
+        Runtime {
+            codec,
+            deadline_margin: self.deadline_margin,
+            reporter: self.reporter,
+            shutdown_hooks: self.shutdown_hooks,
+            shutdown_timeout: self.shutdown_timeout,
+        }
+    }
+
+    /// Cancels a handler that is still running `margin` before the invocation's deadline
+    /// and reports a `TimeoutError` in its place.
+    ///
+    /// By default, handlers run until they complete or Lambda stops the function, in which
+    /// case no error report is sent. `margin` should leave enough time to send the report.
+    #[must_use]
+    pub const fn enforce_deadline(mut self, margin: Duration) -> Self {
+        self.deadline_margin = Some(margin);
+        self
+    }
+
+    /// Transforms the errors returned by the handler, and any that occur while the runtime
+    /// initializes, using `reporter` instead of the hook registered with
+    /// [`set_error_hook`](error_hook::set_error_hook).
+    #[must_use]
+    pub fn error_reporter<R>(mut self, reporter: R) -> Self
+    where
+        R: ErrorReporter + 'static,
+    {
+        self.reporter = Some(Arc::new(reporter));
+        self
+    }
+
+    /// Runs `hook` when Lambda signals that the execution environment is shutting down, for
+    /// example to flush metrics or close connection pools.
+    ///
+    /// Lambda sends SIGTERM to the runtime, then stops it shortly afterwards. Once the
+    /// signal is received, the runtime stops polling for events and runs every hook
+    /// concurrently for up to the [shutdown timeout](Runtime::shutdown_timeout) before
+    /// returning. SIGTERM is only handled by runtimes with at least one hook; otherwise it
+    /// terminates the process as usual.
+    #[must_use]
+    pub fn on_shutdown<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.shutdown_hooks.push(Arc::new(move || hook().boxed()));
+        self
+    }
+
+    /// Sets the time allowed for shutdown hooks to complete, which is 500 ms by default.
+    /// Hooks still running after `timeout` are cancelled.
+    #[must_use]
+    pub const fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = Some(timeout);
+        self
+    }
+
+    /// Runs the shutdown hooks until they complete or the shutdown timeout expires.
+    ///
+    /// The returned future does not borrow the runtime, so it is `Send` whatever the codec.
+    fn shut_down(&self) -> impl Future<Output = ()> {
+        let hooks = future::join_all(self.shutdown_hooks.iter().map(|hook| hook()));
+        let timeout = self.shutdown_timeout.unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT);
+        let timeout = runtime::time::Delay::new(timeout);
+        async move {
+            pin_mut!(hooks);
+            future::select(hooks, timeout).await;
+        }
+    }
+
+    /// Transforms `err` into a report using the runtime's reporter, if any, or the error hook.
+    fn report(&self, err: Err, error_type: Option<String>, ctx: Option<&LambdaCtx>) -> ErrorReport {
+        match &self.reporter {
+            Some(reporter) => reporter.report(err, ctx),
+            None => error_hook::generate_report(err, error_type),
+        }
+    }
+
+    /// Reports `err` as an initialization error, then returns it to stop the runtime.
+    ///
+    /// A copy of `err` is reported, so that the caller receives the original. A failure to
+    /// report is ignored in favor of returning the error that caused it.
+    fn fail_init<'a, C>(
+        &self,
+        client: &'a C,
+        err: Err,
+    ) -> impl Future<Output = Result<(), Err>> + 'a
+    where
+        C: EventClient<'a>,
+    {
+        let (err, error_type) = error_hook::resolve_error_type(err);
+        let copy = error_hook::ErrorCopy::new(&*err);
+        let report = self.report(Box::new(copy), error_type, None);
+        async move {
+            let _ = init_error(client, &report).await;
+            Err(err)
+        }
+    }
+
+    /// Runs `handler` on the raw `event`, returning the encoded response or a report of why
+    /// the invocation failed.
+    pub(crate) async fn invoke<Function, Event, Output>(
+        &self,
+        handler: &mut Function,
+        event: &[u8],
+        ctx: &LambdaCtx,
+    ) -> Result<Bytes, ErrorReport>
+    where
+        Function: Handler<Event, Output>,
+        Format: Codec<Event, Output>,
+    {
+        let event = match self.codec.decode(event) {
+            Ok(event) => event,
+            Err(err) => return Err(self.report(err, None, Some(ctx))),
+        };
+
+        // The handler is invoked inside the `async` block so that panics raised while
+        // creating the future are caught alongside those raised while polling it.
+        let fut =
+            AssertUnwindSafe(async { handler.call(event, Some(ctx.clone())).await }).catch_unwind();
+        let res = match self.deadline_margin {
+            Some(margin) => {
+                let timeout = ctx.deadline_reached(margin);
+                pin_mut!(fut);
+                match future::select(fut, timeout).await {
+                    Either::Left((res, _)) => Some(res),
+                    // Dropping the handler's future cancels it.
+                    Either::Right(_) => None,
+                }
             }
-            Err(err) => {
-                let err = error_hook::generate_report(err.into());
-                let err = serde_json::to_vec(&err)?;
-                let uri = format!("/runtime/invocation/{}/error", &ctx.id).parse::<Uri>()?;
-                let req = Request::builder()
-                    .uri(uri)
-                    .method(Method::POST)
-                    .body(Bytes::from(err))?;
-
-                client.call(req).await?;
+            None => Some(fut.await),
+        };
+        match res {
+            Some(Ok(Ok(res))) => self
+                .codec
+                .encode(res)
+                .map_err(|err| self.report(err, None, Some(ctx))),
+            Some(Ok(Err(err))) => {
+                let (err, error_type) = error_hook::resolve_error_type(err);
+                Err(self.report(err, error_type, Some(ctx)))
             }
+            Some(Err(payload)) => Err(error_hook::panic_report(&*payload)),
+            None => Err(error_hook::timeout_report(ctx.deadline)),
         }
     }
 
+    /// Starts the runtime and begins polling for events on the [Lambda
+    /// Runtime APIs](https://docs.aws.amazon.com/lambda/latest/dg/runtimes-api.html).
+    ///
+    /// # Arguments
+    /// * `handler` - A function or closure that conforms to the `Handler` trait
+    ///
+    /// # Errors
+    /// Returns an error if the runtime fails to initialize or loses its connection to the Runtime APIs.
+    pub async fn run<Function, Event, Output>(self, handler: Function) -> Result<(), Err>
+    where
+        Function: Handler<Event, Output>,
+        Format: Codec<Event, Output>,
+    {
+        let client = Client::new(runtime_api()?);
+        let config = match Config::from_env() {
+            Ok(config) => config,
+            Err(err) => return self.fail_init(&client, err).await,
+        };
+        self.run_with_client(client, config, handler).await
+    }
+
+    /// Starts the runtime, polling for events and reporting their results using `client`
+    /// instead of a client connected to the address in `AWS_LAMBDA_RUNTIME_API`.
+    ///
+    /// This allows the runtime to be driven by an in-memory [`EventClient`] in tests, or by
+    /// a [`Client`] with custom connection settings. The environment is not read: `config`
+    /// is passed to the handler in place of the configuration [`run`](Runtime::run) reads
+    /// from the `AWS_LAMBDA_*` environment variables.
+    ///
+    /// # Errors
+    /// Returns an error if the runtime fails to initialize or `client` fails to deliver a request.
+    pub async fn run_with_client<C, Function, Event, Output>(
+        self,
+        client: C,
+        config: Config,
+        mut handler: Function,
+    ) -> Result<(), Err>
+    where
+        C: for<'a> EventClient<'a>,
+        Function: Handler<Event, Output>,
+        Format: Codec<Event, Output>,
+    {
+        error_hook::capture_panic_locations();
+        // Without hooks to run, SIGTERM is left to terminate the process.
+        let mut sigterm = if self.shutdown_hooks.is_empty() {
+            None
+        } else {
+            match shutdown::Sigterm::new() {
+                Ok(sigterm) => Some(sigterm),
+                Err(err) => return self.fail_init(&client, err.into()).await,
+            }
+        };
+        let mut stream = EventStream::new(&client);
+        let mut initialized = false;
+
+        loop {
+            let event = match &mut sigterm {
+                // The signal is checked first, so that no further events are processed once
+                // it has been received.
+                Some(sigterm) => match future::select(sigterm, stream.next()).await {
+                    Either::Left(_) => {
+                        self.shut_down().await;
+                        break;
+                    }
+                    Either::Right((event, _)) => event,
+                },
+                None => stream.next().await,
+            };
+            let Some(event) = event else { break };
+            let (parts, body) = event?.into_parts();
+            if !parts.status.is_success() {
+                return Err(err_fmt!("Failed to fetch the next event: {}", parts.status).into());
+            }
+            let id = parts.headers.typed_get::<RequestId>();
+            let mut ctx: LambdaCtx = match (LambdaCtx::try_from(parts.headers), id) {
+                (Ok(ctx), _) => ctx,
+                // Once an event has been fetched, the invocation fails if it can be identified.
+                (Err(err), Some(id)) => {
+                    let report = self.report(err, None, None);
+                    invocation_error(&client, &id.0, &report).await?;
+                    continue;
+                }
+                // Otherwise, until an event has been processed, the failure is an
+                // initialization error.
+                (Err(err), None) if !initialized => {
+                    return self.fail_init(&client, err).await;
+                }
+                (Err(err), None) => return Err(err),
+            };
+            initialized = true;
+            ctx.env_config = config.clone();
+
+            match self.invoke(&mut handler, &body, &ctx).await {
+                Ok(res) => {
+                    let uri = format!("/runtime/invocation/{}/response", ctx.id).parse::<Uri>()?;
+                    let req = Request::builder().uri(uri).method(Method::POST).body(res)?;
+
+                    client.call(req).await?;
+                }
+                Err(report) => invocation_error(&client, &ctx.id, &report).await?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Starts the Lambda Rust runtime using `client` to communicate with the Runtime APIs.
+///
+/// See [`Runtime::run_with_client`].
+///
+/// # Errors
+/// Returns an error if the runtime fails to initialize or `client` fails to deliver a request.
+pub async fn run_with_client<C, Function, Event, Output>(
+    client: C,
+    config: Config,
+    handler: Function,
+) -> Result<(), Err>
+where
+    C: for<'a> EventClient<'a>,
+    Function: Handler<Event, Output>,
+    Event: for<'de> Deserialize<'de>,
+    Output: Serialize,
+{
+    Runtime::new()
+        .run_with_client(client, config, handler)
+        .await
+}
+
+/// Reports an error that occurred while initializing the function to the
+/// [Runtime API](https://docs.aws.amazon.com/lambda/latest/dg/runtimes-api.html#runtimes-api-initerror).
+///
+/// Startup code that runs before [`run`]—opening database pools, loading secrets—should
+/// call this before exiting so that Lambda records the failure. The error is converted into
+/// an [`ErrorReport`](error_hook::ErrorReport) using the registered error hook.
+///
+/// # Errors
+/// Returns an error if `AWS_LAMBDA_RUNTIME_API` is unset or the report could not be delivered.
+///
+/// # Example
+/// ```no_run
+/// #![feature(async_await)]
+///
+/// use lambda::{handler_fn, LambdaCtx};
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// #[runtime::main]
+/// async fn main() -> Result<(), Err> {
+///     if let Err(err) = std::env::var("TABLE_NAME") {
+///         lambda::report_init_error(err).await?;
+///         return Ok(());
+///     }
+///     let func = handler_fn(func);
+///     lambda::run(func).await?;
+///     Ok(())
+/// }
+///
+/// async fn func(event: String, _ctx: Option<LambdaCtx>) -> Result<String, Err> {
+///     Ok(event)
+/// }
+/// ```
+pub async fn report_init_error<E: Into<Err>>(err: E) -> Result<(), Err> {
+    let client = Client::new(runtime_api()?);
+    let report = error_hook::report_error(err);
+    init_error(&client, &report).await
+}
+
+/// Reads the address of the Runtime API from the `AWS_LAMBDA_RUNTIME_API` environment variable.
+pub(crate) fn runtime_api() -> Result<Uri, Err> {
+    let api = env::var("AWS_LAMBDA_RUNTIME_API")?;
+    // Lambda provides the address of the Runtime API without a scheme.
+    let uri: Bytes = if api.contains("://") {
+        api.into()
+    } else {
+        format!("http://{api}").into()
+    };
+    Ok(Uri::from_shared(uri)?)
+}
+
+/// Posts `report` to the error endpoint of the invocation identified by `id`.
+async fn invocation_error<'a, C>(client: &'a C, id: &str, report: &ErrorReport) -> Result<(), Err>
+where
+    C: EventClient<'a>,
+{
+    let report = serde_json::to_vec(report)?;
+    let uri = format!("/runtime/invocation/{id}/error").parse::<Uri>()?;
+    let req = Request::builder()
+        .uri(uri)
+        .method(Method::POST)
+        .body(Bytes::from(report))?;
+
+    client.call(req).await?;
+    Ok(())
+}
+
+/// Posts `report` to the initialization error endpoint.
+async fn init_error<'a, C>(client: &'a C, report: &ErrorReport) -> Result<(), Err>
+where
+    C: EventClient<'a>,
+{
+    let report = serde_json::to_vec(report)?;
+    let req = Request::builder()
+        .uri(Uri::from_static("/runtime/init/error"))
+        .method(Method::POST)
+        .body(Bytes::from(report))?;
+
+    client.call(req).await?;
     Ok(())
 }
 
@@ -234,8 +701,151 @@ async fn get_next() -> Result<(), Err> {
         Ok(req)
     }
 
+    let emulator = emulator::Emulator::start()?;
+    let invocation = emulator::Invocation::new(r#""hello""#);
+    let id = invocation.id().to_string();
+    emulator.push(invocation);
+
     let test_fn = handler_fn(test_fn);
-    let _ = run(test_fn).await?;
+    let _ = Runtime::new()
+        .run_with_client(emulator.client(), emulator.config(), test_fn)
+        .await;
 
+    let expected = emulator::Outcome::Response(Bytes::from(r#""hello""#));
+    assert_eq!(Some(expected), emulator.outcome(&id));
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::EventClient, error_hook::ErrorReport, handler_fn, testing, Config, Err, LambdaCtx,
+        Runtime,
+    };
+    use bytes::Bytes;
+    use futures::future::{self, Ready};
+    use http::{Request, Response};
+    use runtime::time::Delay;
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    /// An `EventClient` that serves queued events and records every other request. Once
+    /// the queue is empty, polling for the next event fails, which stops the runtime.
+    #[derive(Default, Clone)]
+    struct FakeClient {
+        events: Arc<Mutex<VecDeque<Response<Bytes>>>>,
+        requests: Arc<Mutex<Vec<Request<Bytes>>>>,
+    }
+
+    impl FakeClient {
+        fn push_event(&self, id: &str, deadline: Duration, body: &str) {
+            let deadline = SystemTime::now() + deadline;
+            let deadline = deadline.duration_since(UNIX_EPOCH).unwrap().as_millis();
+            let res = Response::builder()
+                .header("lambda-runtime-aws-request-id", id)
+                .header("lambda-runtime-deadline-ms", deadline.to_string())
+                .header(
+                    "lambda-runtime-invoked-function-arn",
+                    "arn:aws:lambda:us-east-1:123456789012:function:test",
+                )
+                .body(Bytes::from(body))
+                .unwrap();
+            self.events.lock().unwrap().push_back(res);
+        }
+
+        fn requests(&self) -> Vec<(String, Bytes)> {
+            let requests = self.requests.lock().unwrap();
+            requests
+                .iter()
+                .map(|req| (req.uri().path().to_string(), req.body().clone()))
+                .collect()
+        }
+    }
+
+    impl<'a> EventClient<'a> for FakeClient {
+        type Fut = Ready<Result<Response<Bytes>, Err>>;
+
+        fn call(&self, req: Request<Bytes>) -> Self::Fut {
+            if req.uri().path() == "/runtime/invocation/next" {
+                let event = self.events.lock().unwrap().pop_front();
+                return future::ready(event.ok_or_else(|| err_fmt!("no more events").into()));
+            }
+            self.requests.lock().unwrap().push(req);
+            future::ready(Ok(Response::new(Bytes::new())))
+        }
+    }
+
+    fn config() -> Config {
+        testing::CtxBuilder::new().build().env_config
+    }
+
+    async fn func(event: String, ctx: Option<LambdaCtx>) -> Result<String, Err> {
+        match event.as_str() {
+            "name" => Ok(ctx.unwrap().env_config.function_name),
+            "panic" => panic!("unexpected event"),
+            "sleep" => {
+                Delay::new(Duration::from_secs(10)).await;
+                Ok(event)
+            }
+            _ => Ok(event),
+        }
+    }
+
+    #[runtime::test]
+    async fn fail_init_reports_to_init_error() {
+        let client = FakeClient::default();
+        let err = std::io::Error::new(std::io::ErrorKind::NotFound, err_fmt!("missing secret"));
+        let res = Runtime::new().fail_init(&client, err.into()).await;
+        // The caller receives the original error, along with its type and sources.
+        let err = res.unwrap_err();
+        let err = err.downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(std::io::ErrorKind::NotFound, err.kind());
+
+        let requests = client.requests();
+        assert_eq!(1, requests.len());
+        assert_eq!("/runtime/init/error", requests[0].0);
+        let report: ErrorReport = serde_json::from_slice(&requests[0].1).unwrap();
+        assert_eq!("missing secret", report.err);
+    }
+
+    #[runtime::test]
+    async fn run_with_client() {
+        let client = FakeClient::default();
+        client.push_event("1", Duration::from_secs(60), "\"hello\"");
+        client.push_event("2", Duration::from_secs(60), "\"panic\"");
+        client.push_event("3", Duration::from_secs(60), "\"name\"");
+
+        let res = super::run_with_client(client.clone(), config(), handler_fn(func)).await;
+        assert_eq!("no more events", res.unwrap_err().to_string());
+
+        let requests = client.requests();
+        assert_eq!(3, requests.len());
+        assert_eq!("/runtime/invocation/1/response", requests[0].0);
+        assert_eq!(Bytes::from("\"hello\""), requests[0].1);
+        assert_eq!("/runtime/invocation/2/error", requests[1].0);
+        let report: ErrorReport = serde_json::from_slice(&requests[1].1).unwrap();
+        assert_eq!("Panic", report.name);
+        assert_eq!("/runtime/invocation/3/response", requests[2].0);
+        assert_eq!(Bytes::from("\"test\""), requests[2].1);
+    }
+
+    #[runtime::test]
+    async fn enforce_deadline() {
+        let client = FakeClient::default();
+        client.push_event("1", Duration::from_millis(300), "\"sleep\"");
+
+        let runtime = Runtime::new().enforce_deadline(Duration::from_millis(100));
+        let res = runtime
+            .run_with_client(client.clone(), config(), handler_fn(func))
+            .await;
+        assert!(res.is_err());
+
+        let requests = client.requests();
+        assert_eq!("/runtime/invocation/1/error", requests[0].0);
+        let report: ErrorReport = serde_json::from_slice(&requests[0].1).unwrap();
+        assert_eq!("TimeoutError", report.name);
+    }
+}
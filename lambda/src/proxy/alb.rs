@@ -0,0 +1,214 @@
+use super::ProxyResponse;
+use crate::Err;
+use bytes::Bytes;
+use http::Request;
+use serde::{ser::SerializeStruct, Deserialize, Serializer};
+use std::collections::HashMap;
+
+/// The context of a request received from an Application Load Balancer.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct AlbRequestContext {
+    /// The load balancer that received the request.
+    pub elb: ElbContext,
+}
+
+/// The load balancer that received a request.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ElbContext {
+    /// The ARN of the target group the function is registered with.
+    pub target_group_arn: String,
+}
+
+/// An event sent by an Application Load Balancer to a [Lambda
+/// target](https://docs.aws.amazon.com/elasticloadbalancing/latest/application/lambda-functions.html).
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ProxyEvent {
+    http_method: String,
+    path: String,
+    headers: Option<HashMap<String, String>>,
+    multi_value_headers: Option<HashMap<String, Vec<String>>>,
+    query_string_parameters: Option<HashMap<String, String>>,
+    multi_value_query_string_parameters: Option<HashMap<String, Vec<String>>>,
+    request_context: AlbRequestContext,
+    body: Option<String>,
+    #[serde(default)]
+    is_base64_encoded: bool,
+}
+
+impl ProxyEvent {
+    /// Converts the event into the request it represents.
+    pub(super) fn into_request(self) -> Result<Request<Bytes>, Err> {
+        let uri = match query_string(
+            self.query_string_parameters,
+            self.multi_value_query_string_parameters,
+        ) {
+            Some(query) => format!("{}?{}", self.path, query),
+            None => self.path,
+        };
+        let body = super::decode_body(self.body, self.is_base64_encoded)?;
+        let mut req = Request::builder()
+            .method(self.http_method.as_str())
+            .uri(uri.as_str())
+            .body(body)?;
+
+        super::insert_headers(req.headers_mut(), self.headers, self.multi_value_headers)?;
+        req.extensions_mut().insert(self.request_context);
+        Ok(req)
+    }
+}
+
+/// Builds the query string of a request. Unlike API Gateway, load balancers do not decode
+/// the parameters, so they are joined as they are.
+fn query_string(
+    params: Option<HashMap<String, String>>,
+    multi_value_params: Option<HashMap<String, Vec<String>>>,
+) -> Option<String> {
+    let mut pairs: Vec<String> = multi_value_params.map_or_else(
+        || {
+            let params = params.unwrap_or_default().into_iter();
+            params
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect()
+        },
+        |params| {
+            let params = params.into_iter().flat_map(|(name, values)| {
+                let pairs = values.into_iter();
+                pairs.map(move |value| format!("{name}={value}"))
+            });
+            params.collect()
+        },
+    );
+    if pairs.is_empty() {
+        return None;
+    }
+    pairs.sort();
+    Some(pairs.join("&"))
+}
+
+/// Serializes `res` in the shape expected from a Lambda target, using multi-value headers
+/// if the request did.
+pub(super) fn serialize<S: Serializer>(
+    res: &ProxyResponse,
+    multi_value_headers: bool,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let (body, is_base64_encoded) = res.encode_body();
+    let res = &res.response;
+    let status = res.status();
+    let description = format!(
+        "{} {}",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or_default()
+    );
+    let headers = super::multi_value_headers(res.headers());
+
+    let mut state = serializer.serialize_struct("AlbResponse", 5)?;
+    state.serialize_field("statusCode", &status.as_u16())?;
+    state.serialize_field("statusDescription", description.trim_end())?;
+    if multi_value_headers {
+        state.serialize_field("multiValueHeaders", &headers)?;
+    } else {
+        // Without multi-value headers, only the last value of each header is kept.
+        let headers: HashMap<&str, String> = headers
+            .into_iter()
+            .filter_map(|(name, mut values)| values.pop().map(|value| (name, value)))
+            .collect();
+        state.serialize_field("headers", &headers)?;
+    }
+    state.serialize_field("body", &body)?;
+    state.serialize_field("isBase64Encoded", &is_base64_encoded)?;
+    state.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{ProxyRequest, ProxyResponse, RequestExt, Source};
+    use http::{Method, Response, StatusCode};
+
+    #[test]
+    fn deserialize_request() {
+        let event = include_str!("../../tests/events/alb-request.json");
+        let req: ProxyRequest = serde_json::from_str(event).unwrap();
+        assert_eq!(
+            Source::Alb {
+                multi_value_headers: false
+            },
+            req.source
+        );
+        let req = req.request;
+
+        assert_eq!(Method::GET, *req.method());
+        assert_eq!("/lambda?query=1234%20ABCD", req.uri().to_string());
+        assert_eq!("", req.body());
+        assert_eq!("curl/7.54.0", req.headers()["user-agent"]);
+        let ctx = req.alb_context().unwrap();
+        assert_eq!(
+            "arn:aws:elasticloadbalancing:us-east-1:123456789012:targetgroup/lambda/1234",
+            ctx.elb.target_group_arn
+        );
+    }
+
+    #[test]
+    fn serialize_response() {
+        let response = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header("set-cookie", "a=1")
+            .header("set-cookie", "b=2")
+            .body("missing".into())
+            .unwrap();
+        let res = ProxyResponse {
+            source: Source::Alb {
+                multi_value_headers: false,
+            },
+            response,
+            binary: false,
+        };
+        let res = serde_json::to_value(res).unwrap();
+        assert_eq!(404, res["statusCode"]);
+        assert_eq!("404 Not Found", res["statusDescription"]);
+        assert_eq!("b=2", res["headers"]["set-cookie"]);
+        assert_eq!(serde_json::Value::Null, res["multiValueHeaders"]);
+    }
+
+    #[test]
+    fn multi_value_headers() {
+        let event = r#"{
+            "requestContext": {"elb": {"targetGroupArn": "arn"}},
+            "httpMethod": "GET",
+            "path": "/",
+            "multiValueQueryStringParameters": {"tag": ["a", "b"]},
+            "multiValueHeaders": {"accept": ["text/html", "application/json"]},
+            "body": "",
+            "isBase64Encoded": false
+        }"#;
+        let req: ProxyRequest = serde_json::from_str(event).unwrap();
+        let source = req.source;
+        assert_eq!(
+            Source::Alb {
+                multi_value_headers: true
+            },
+            source
+        );
+        assert_eq!("/?tag=a&tag=b", req.request.uri().to_string());
+        assert_eq!(2, req.request.headers().get_all("accept").iter().count());
+
+        let response = Response::builder()
+            .header("set-cookie", "a=1")
+            .header("set-cookie", "b=2")
+            .body("".into())
+            .unwrap();
+        let res = serde_json::to_value(ProxyResponse {
+            source,
+            response,
+            binary: false,
+        })
+        .unwrap();
+        assert_eq!(
+            serde_json::json!(["a=1", "b=2"]),
+            res["multiValueHeaders"]["set-cookie"]
+        );
+    }
+}
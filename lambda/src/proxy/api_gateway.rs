@@ -0,0 +1,152 @@
+use super::{PathParameters, ProxyResponse, StageVariables};
+use crate::Err;
+use bytes::Bytes;
+use http::Request;
+use serde::{ser::SerializeStruct, Deserialize, Serializer};
+use std::collections::HashMap;
+
+/// The context of a request received from an API Gateway REST API.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApiGatewayRequestContext {
+    /// The ID of the AWS account that owns the API.
+    pub account_id: String,
+    /// The ID of the API.
+    pub api_id: String,
+    /// The ID of the resource that matched the request.
+    pub resource_id: String,
+    /// The path of the resource that matched the request, such as `/pets/{id}`.
+    pub resource_path: String,
+    /// The name of the stage the request was sent to.
+    pub stage: String,
+    /// The ID API Gateway assigned to the request.
+    pub request_id: String,
+    /// The HTTP method of the request.
+    pub http_method: String,
+    /// The full path of the request, including the stage.
+    pub path: Option<String>,
+    /// The identity of the caller.
+    pub identity: ApiGatewayIdentity,
+    /// The output of the authorizer of the route, if any.
+    pub authorizer: Option<serde_json::Value>,
+}
+
+/// The identity of the caller of an API Gateway REST API.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApiGatewayIdentity {
+    /// The IP address of the caller.
+    pub source_ip: String,
+    /// The user agent of the caller.
+    pub user_agent: Option<String>,
+    /// The ARN of the caller, if it was authenticated with IAM.
+    pub user_arn: Option<String>,
+    /// The API key used by the caller, if any.
+    pub api_key: Option<String>,
+    /// The Cognito identity ID of the caller, if any.
+    pub cognito_identity_id: Option<String>,
+}
+
+/// An event sent by an API Gateway REST API [proxy
+/// integration](https://docs.aws.amazon.com/apigateway/latest/developerguide/set-up-lambda-proxy-integrations.html).
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ProxyEvent {
+    http_method: String,
+    path: String,
+    headers: Option<HashMap<String, String>>,
+    multi_value_headers: Option<HashMap<String, Vec<String>>>,
+    query_string_parameters: Option<HashMap<String, String>>,
+    multi_value_query_string_parameters: Option<HashMap<String, Vec<String>>>,
+    path_parameters: Option<HashMap<String, String>>,
+    stage_variables: Option<HashMap<String, String>>,
+    request_context: ApiGatewayRequestContext,
+    body: Option<String>,
+    #[serde(default)]
+    is_base64_encoded: bool,
+}
+
+impl ProxyEvent {
+    /// Converts the event into the request it represents.
+    pub(super) fn into_request(self) -> Result<Request<Bytes>, Err> {
+        let uri = match super::query_string(
+            self.query_string_parameters,
+            self.multi_value_query_string_parameters,
+        ) {
+            Some(query) => format!("{}?{}", self.path, query),
+            None => self.path,
+        };
+        let body = super::decode_body(self.body, self.is_base64_encoded)?;
+        let mut req = Request::builder()
+            .method(self.http_method.as_str())
+            .uri(uri.as_str())
+            .body(body)?;
+
+        super::insert_headers(req.headers_mut(), self.headers, self.multi_value_headers)?;
+        let extensions = req.extensions_mut();
+        extensions.insert(PathParameters(self.path_parameters.unwrap_or_default()));
+        extensions.insert(StageVariables(self.stage_variables.unwrap_or_default()));
+        extensions.insert(self.request_context);
+        Ok(req)
+    }
+}
+
+/// Serializes `res` in the shape expected from a proxy integration.
+pub(super) fn serialize<S: Serializer>(
+    res: &ProxyResponse,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let (body, is_base64_encoded) = res.encode_body();
+    let res = &res.response;
+    let mut state = serializer.serialize_struct("ProxyResponse", 4)?;
+    state.serialize_field("statusCode", &res.status().as_u16())?;
+    state.serialize_field(
+        "multiValueHeaders",
+        &super::multi_value_headers(res.headers()),
+    )?;
+    state.serialize_field("body", &body)?;
+    state.serialize_field("isBase64Encoded", &is_base64_encoded)?;
+    state.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{ProxyRequest, RequestExt};
+    use http::Method;
+
+    #[test]
+    fn deserialize_request() {
+        let event = include_str!("../../tests/events/apigw-proxy-request.json");
+        let req: ProxyRequest = serde_json::from_str(event).unwrap();
+        let req = req.request;
+
+        assert_eq!(Method::POST, *req.method());
+        assert_eq!("/hello/world?name=me&tag=a&tag=b", req.uri().to_string());
+        assert_eq!("hello", req.body());
+        assert_eq!(2, req.headers().get_all("accept").iter().count());
+        assert_eq!("world", req.path_parameters().0["proxy"]);
+        assert_eq!("prod", req.stage_variables().0["env"]);
+        let ctx = req.api_gateway_context().unwrap();
+        assert_eq!("c6af9ac6-7b61-11e6-9a41-93e8deadbeef", ctx.request_id);
+        assert_eq!("192.168.100.1", ctx.identity.source_ip);
+        assert_eq!(None, req.lambda_context());
+    }
+
+    #[test]
+    fn deserialize_base64_body() {
+        let event = r#"{
+            "httpMethod": "PUT",
+            "path": "/upload",
+            "headers": {"Content-Type": "application/octet-stream"},
+            "multiValueHeaders": null,
+            "requestContext": {},
+            "body": "//4=",
+            "isBase64Encoded": true
+        }"#;
+        let req: ProxyRequest = serde_json::from_str(event).unwrap();
+        let req = req.request;
+        assert_eq!(&[0xff, 0xfe][..], &req.body()[..]);
+        assert_eq!("application/octet-stream", req.headers()["content-type"]);
+        assert_eq!("/upload", req.uri().to_string());
+    }
+}
@@ -0,0 +1,170 @@
+use super::{PathParameters, ProxyResponse, StageVariables};
+use crate::Err;
+use bytes::Bytes;
+use http::{header::COOKIE, header::SET_COOKIE, HeaderValue, Request};
+use serde::{ser::SerializeStruct, Deserialize, Serializer};
+use std::collections::HashMap;
+
+/// The context of a request received from an API Gateway HTTP API or a Lambda function URL.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HttpApiRequestContext {
+    /// The ID of the AWS account that owns the API or function.
+    pub account_id: String,
+    /// The ID of the API, or of the function URL.
+    pub api_id: String,
+    /// The domain name the request was sent to.
+    pub domain_name: String,
+    /// The first label of the domain name.
+    pub domain_prefix: String,
+    /// A description of the HTTP request.
+    pub http: HttpDescription,
+    /// The ID assigned to the request.
+    pub request_id: String,
+    /// The key of the route that matched the request, such as `GET /pets`.
+    pub route_key: String,
+    /// The name of the stage the request was sent to.
+    pub stage: String,
+    /// When the request was received, in Unix time milliseconds.
+    pub time_epoch: u64,
+    /// The output of the authorizer of the route, if any.
+    pub authorizer: Option<serde_json::Value>,
+}
+
+/// A description of a request received from an API Gateway HTTP API or a Lambda function URL.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HttpDescription {
+    /// The HTTP method of the request.
+    pub method: String,
+    /// The path of the request.
+    pub path: String,
+    /// The protocol of the request, such as `HTTP/1.1`.
+    pub protocol: String,
+    /// The IP address of the caller.
+    pub source_ip: String,
+    /// The user agent of the caller.
+    pub user_agent: String,
+}
+
+/// An event in the [2.0 payload
+/// format](https://docs.aws.amazon.com/apigateway/latest/developerguide/http-api-develop-integrations-lambda.html),
+/// sent by API Gateway HTTP APIs and Lambda function URLs.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ProxyEvent {
+    raw_path: String,
+    #[serde(default)]
+    raw_query_string: String,
+    cookies: Option<Vec<String>>,
+    headers: Option<HashMap<String, String>>,
+    path_parameters: Option<HashMap<String, String>>,
+    stage_variables: Option<HashMap<String, String>>,
+    request_context: HttpApiRequestContext,
+    body: Option<String>,
+    #[serde(default)]
+    is_base64_encoded: bool,
+}
+
+impl ProxyEvent {
+    /// Converts the event into the request it represents.
+    pub(super) fn into_request(self) -> Result<Request<Bytes>, Err> {
+        let uri = if self.raw_query_string.is_empty() {
+            self.raw_path
+        } else {
+            format!("{}?{}", self.raw_path, self.raw_query_string)
+        };
+        let body = super::decode_body(self.body, self.is_base64_encoded)?;
+        let mut req = Request::builder()
+            .method(self.request_context.http.method.as_str())
+            .uri(uri.as_str())
+            .body(body)?;
+
+        // Headers with several values are already joined with commas.
+        super::insert_headers(req.headers_mut(), self.headers, None)?;
+        // Cookies are sent separately, and are restored to the header they came from.
+        if let Some(cookies) = self.cookies.filter(|cookies| !cookies.is_empty()) {
+            let cookies = HeaderValue::from_str(&cookies.join("; "))?;
+            req.headers_mut().insert(COOKIE, cookies);
+        }
+        let extensions = req.extensions_mut();
+        extensions.insert(PathParameters(self.path_parameters.unwrap_or_default()));
+        extensions.insert(StageVariables(self.stage_variables.unwrap_or_default()));
+        extensions.insert(self.request_context);
+        Ok(req)
+    }
+}
+
+/// Serializes `res` in the shape of a 2.0 payload format response.
+pub(super) fn serialize<S: Serializer>(
+    res: &ProxyResponse,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let (body, is_base64_encoded) = res.encode_body();
+    let res = &res.response;
+    let mut headers = super::multi_value_headers(res.headers());
+    let cookies = headers.remove(SET_COOKIE.as_str()).unwrap_or_default();
+    let headers: HashMap<&str, String> = headers
+        .into_iter()
+        .map(|(name, values)| (name, values.join(",")))
+        .collect();
+
+    let mut state = serializer.serialize_struct("ProxyResponse", 5)?;
+    state.serialize_field("statusCode", &res.status().as_u16())?;
+    state.serialize_field("headers", &headers)?;
+    state.serialize_field("cookies", &cookies)?;
+    state.serialize_field("body", &body)?;
+    state.serialize_field("isBase64Encoded", &is_base64_encoded)?;
+    state.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{ProxyRequest, ProxyResponse, RequestExt, Source};
+    use http::{Method, Response};
+
+    #[test]
+    fn deserialize_request() {
+        let event = include_str!("../../tests/events/http-api-request.json");
+        let req: ProxyRequest = serde_json::from_str(event).unwrap();
+        assert_eq!(Source::HttpApi, req.source);
+        let req = req.request;
+
+        assert_eq!(Method::POST, *req.method());
+        assert_eq!(
+            "/my/path?parameter1=value1&parameter1=value2&parameter2=value",
+            req.uri().to_string()
+        );
+        assert_eq!("Hello from Lambda", req.body());
+        assert_eq!("cookie1=value1; cookie2=value2", req.headers()["cookie"]);
+        assert_eq!("value1,value2", req.headers()["header2"]);
+        assert_eq!("value", req.path_parameters().0["parameter1"]);
+        let ctx = req.http_api_context().unwrap();
+        assert_eq!("$default", ctx.route_key);
+        assert_eq!("192.0.2.1", ctx.http.source_ip);
+        assert_eq!(None, req.api_gateway_context());
+    }
+
+    #[test]
+    fn serialize_response() {
+        let response = Response::builder()
+            .header("content-type", "text/plain")
+            .header("set-cookie", "a=1")
+            .header("set-cookie", "b=2")
+            .header("vary", "accept")
+            .header("vary", "accept-encoding")
+            .body("ok".into())
+            .unwrap();
+        let res = ProxyResponse {
+            source: Source::HttpApi,
+            response,
+            binary: false,
+        };
+        let res = serde_json::to_value(res).unwrap();
+        assert_eq!(200, res["statusCode"]);
+        assert_eq!(serde_json::json!(["a=1", "b=2"]), res["cookies"]);
+        assert_eq!("accept,accept-encoding", res["headers"]["vary"]);
+        assert_eq!("text/plain", res["headers"]["content-type"]);
+        assert_eq!("ok", res["body"]);
+    }
+}
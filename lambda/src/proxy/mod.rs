@@ -0,0 +1,519 @@
+use crate::{Handler, HttpHandler, LambdaCtx};
+use bytes::Bytes;
+use futures::{future::BoxFuture, prelude::*};
+use http::{
+    header::{HeaderName, CONTENT_ENCODING, CONTENT_TYPE},
+    HeaderMap, HeaderValue, Request, Response,
+};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+mod alb;
+mod api_gateway;
+mod http_api;
+
+pub use alb::{AlbRequestContext, ElbContext};
+pub use api_gateway::{ApiGatewayIdentity, ApiGatewayRequestContext};
+pub use http_api::{HttpApiRequestContext, HttpDescription};
+
+/// The path parameters of a request, matched by the resource path of an API Gateway route.
+/// Available in the extensions of a request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathParameters(pub HashMap<String, String>);
+
+/// The stage variables of the API Gateway stage a request was sent to. Available in the
+/// extensions of a request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StageVariables(pub HashMap<String, String>);
+
+/// Convenience accessors for the data an HTTP integration attaches to a request.
+pub trait RequestExt {
+    /// The path parameters of the request, which are empty if there are none.
+    fn path_parameters(&self) -> PathParameters;
+    /// The stage variables of the request, which are empty if there are none.
+    fn stage_variables(&self) -> StageVariables;
+    /// The context of a request received from an API Gateway REST API.
+    fn api_gateway_context(&self) -> Option<&ApiGatewayRequestContext>;
+    /// The context of a request received from an API Gateway HTTP API or a Lambda function URL.
+    fn http_api_context(&self) -> Option<&HttpApiRequestContext>;
+    /// The context of a request received from an Application Load Balancer.
+    fn alb_context(&self) -> Option<&AlbRequestContext>;
+    /// The context of the invocation.
+    fn lambda_context(&self) -> Option<&LambdaCtx>;
+}
+
+impl<B> RequestExt for Request<B> {
+    fn path_parameters(&self) -> PathParameters {
+        self.extensions()
+            .get::<PathParameters>()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn stage_variables(&self) -> StageVariables {
+        self.extensions()
+            .get::<StageVariables>()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn api_gateway_context(&self) -> Option<&ApiGatewayRequestContext> {
+        self.extensions().get::<ApiGatewayRequestContext>()
+    }
+
+    fn http_api_context(&self) -> Option<&HttpApiRequestContext> {
+        self.extensions().get::<HttpApiRequestContext>()
+    }
+
+    fn alb_context(&self) -> Option<&AlbRequestContext> {
+        self.extensions().get::<AlbRequestContext>()
+    }
+
+    fn lambda_context(&self) -> Option<&LambdaCtx> {
+        self.extensions().get::<LambdaCtx>()
+    }
+}
+
+/// The integration an event was received from, which determines the shape of the response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    /// An API Gateway REST API, or an HTTP API using the 1.0 payload format.
+    ApiGateway,
+    /// An API Gateway HTTP API using the 2.0 payload format, or a Lambda function URL.
+    HttpApi,
+    /// An Application Load Balancer, which expects multi-value headers in the response if
+    /// the request had them.
+    Alb { multi_value_headers: bool },
+}
+
+impl Source {
+    /// Detects the integration that sent `event` from its shape.
+    fn detect(event: &serde_json::Value) -> Self {
+        if event["version"] == "2.0" {
+            Self::HttpApi
+        } else if event["requestContext"]["elb"].is_object() {
+            Self::Alb {
+                multi_value_headers: event["multiValueHeaders"].is_object(),
+            }
+        } else {
+            Self::ApiGateway
+        }
+    }
+}
+
+/// An HTTP request received from API Gateway, a Lambda function URL or an Application Load
+/// Balancer.
+///
+/// The integration is detected from the shape of the event, so one handler can serve
+/// several of them. The event is converted while it is deserialized, so that malformed
+/// events are reported as invocation errors.
+#[derive(Debug)]
+pub struct ProxyRequest {
+    source: Source,
+    request: Request<Bytes>,
+}
+
+impl<'de> Deserialize<'de> for ProxyRequest {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let event = serde_json::Value::deserialize(deserializer)?;
+        let source = Source::detect(&event);
+        let request = match source {
+            Source::ApiGateway => serde_json::from_value::<api_gateway::ProxyEvent>(event)
+                .map_err(D::Error::custom)?
+                .into_request(),
+            Source::HttpApi => serde_json::from_value::<http_api::ProxyEvent>(event)
+                .map_err(D::Error::custom)?
+                .into_request(),
+            Source::Alb { .. } => serde_json::from_value::<alb::ProxyEvent>(event)
+                .map_err(D::Error::custom)?
+                .into_request(),
+        };
+        let request = request.map_err(D::Error::custom)?;
+        Ok(Self { source, request })
+    }
+}
+
+/// An HTTP response, serialized in the shape expected by the integration the request was
+/// received from.
+#[derive(Debug)]
+pub struct ProxyResponse {
+    source: Source,
+    response: Response<Bytes>,
+    /// Whether the body must be encoded as base64, regardless of its content.
+    binary: bool,
+}
+
+impl ProxyResponse {
+    /// Encodes the body as text, or as base64 if it is binary, returning whether it was
+    /// encoded as base64.
+    fn encode_body(&self) -> (String, bool) {
+        let body = self.response.body();
+        match std::str::from_utf8(body) {
+            Ok(text) if !self.binary => (text.to_string(), false),
+            _ => (base64::encode(body), true),
+        }
+    }
+}
+
+impl Serialize for ProxyResponse {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.source {
+            Source::ApiGateway => api_gateway::serialize(self, serializer),
+            Source::HttpApi => http_api::serialize(self, serializer),
+            Source::Alb {
+                multi_value_headers,
+            } => alb::serialize(self, multi_value_headers, serializer),
+        }
+    }
+}
+
+/// The content types whose bodies are sent as text, unless configured otherwise.
+const TEXT_CONTENT_TYPES: &[&str] = &[
+    "text/*",
+    "application/json",
+    "application/javascript",
+    "application/xml",
+    "application/x-www-form-urlencoded",
+    "*+json",
+    "*+xml",
+];
+
+/// Decides whether the bodies of responses are sent as text or as base64, which the
+/// integrations require for binary content.
+#[derive(Debug, Clone)]
+struct BodyEncoding {
+    text_content_types: Arc<Vec<String>>,
+}
+
+impl Default for BodyEncoding {
+    fn default() -> Self {
+        let types = TEXT_CONTENT_TYPES.iter().map(|ty| (*ty).to_string());
+        Self {
+            text_content_types: Arc::new(types.collect()),
+        }
+    }
+}
+
+impl BodyEncoding {
+    /// Whether the body of `res` is binary. Compressed bodies are always binary. Otherwise,
+    /// bodies are binary unless their content type is a text type. Bodies without a content
+    /// type are sent as text if they are valid UTF-8.
+    fn is_binary<B>(&self, res: &Response<B>) -> bool {
+        let headers = res.headers();
+        let encoding = headers.get(CONTENT_ENCODING).map(HeaderValue::as_bytes);
+        let compressed = encoding.is_some_and(|encoding| encoding != b"identity");
+        let content_type = headers.get(CONTENT_TYPE).and_then(|ty| ty.to_str().ok());
+        compressed || content_type.is_some_and(|ty| !self.is_text(ty))
+    }
+
+    /// Whether `content_type` is one of the text content types.
+    fn is_text(&self, content_type: &str) -> bool {
+        // Parameters such as `charset` do not affect the encoding.
+        let essence = content_type.split(';').next().unwrap_or_default();
+        let essence = essence.trim().to_ascii_lowercase();
+        self.text_content_types
+            .iter()
+            .any(|pattern| content_type_matches(pattern, &essence))
+    }
+}
+
+/// Whether the content type `essence` matches `pattern`, which is either a content type, a
+/// type with any subtype such as `text/*`, or a structured syntax suffix such as `*+json`.
+fn content_type_matches(pattern: &str, essence: &str) -> bool {
+    if pattern.ends_with("/*") {
+        essence.starts_with(&pattern[..pattern.len() - 1])
+    } else if pattern.starts_with("*+") {
+        essence.ends_with(&pattern[1..])
+    } else {
+        pattern.eq_ignore_ascii_case(essence)
+    }
+}
+
+/// Runs an [`HttpHandler`] as a [`Handler`] of the events sent by HTTP integrations.
+///
+/// # Example
+/// ```no_run
+/// #![feature(async_await)]
+///
+/// use bytes::Bytes;
+/// use http::{Request, Response};
+/// use lambda::{handler_fn, proxy, LambdaCtx};
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// #[runtime::main]
+/// async fn main() -> Result<(), Err> {
+///     lambda::run(proxy::adapter(handler_fn(hello))).await?;
+///     Ok(())
+/// }
+///
+/// async fn hello(req: Request<Bytes>, _ctx: Option<LambdaCtx>) -> Result<Response<String>, Err> {
+///     Ok(Response::new(format!("Hello from {}", req.uri().path())))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Adapter<H, Body> {
+    handler: H,
+    encoding: BodyEncoding,
+    body: PhantomData<fn() -> Body>,
+}
+
+impl<H, Body> Adapter<H, Body> {
+    /// Sets the content types whose bodies are sent as text. The bodies of responses with
+    /// other content types, or with a `Content-Encoding` such as `gzip`, are encoded as
+    /// base64.
+    ///
+    /// Each entry is a content type such as `application/json`, a type with any subtype such
+    /// as `text/*`, or a structured syntax suffix such as `*+json`. By default, `text/*`,
+    /// `application/json`, `application/javascript`, `application/xml`,
+    /// `application/x-www-form-urlencoded`, `*+json` and `*+xml` are sent as text.
+    #[must_use]
+    pub fn text_content_types<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let types = types.into_iter().map(|ty| ty.into().to_ascii_lowercase());
+        self.encoding.text_content_types = Arc::new(types.collect());
+        self
+    }
+}
+
+/// Returns an [`Adapter`] that runs `handler`.
+pub fn adapter<H, Body>(handler: H) -> Adapter<H, Body>
+where
+    H: HttpHandler<Body>,
+    Body: Into<Bytes>,
+{
+    Adapter {
+        handler,
+        encoding: BodyEncoding::default(),
+        body: PhantomData,
+    }
+}
+
+impl<H, Body> Handler<ProxyRequest, ProxyResponse> for Adapter<H, Body>
+where
+    H: HttpHandler<Body>,
+    H::Fut: Send + 'static,
+    Body: Into<Bytes>,
+{
+    type Err = H::Err;
+    type Fut = BoxFuture<'static, Result<ProxyResponse, H::Err>>;
+
+    fn call(&mut self, event: ProxyRequest, ctx: Option<LambdaCtx>) -> Self::Fut {
+        let ProxyRequest {
+            source,
+            mut request,
+        } = event;
+        if let Some(ctx) = ctx {
+            request.extensions_mut().insert(ctx);
+        }
+        let fut = self.handler.call_http(request);
+        let encoding = self.encoding.clone();
+        async move {
+            let (parts, body) = fut.await?.into_parts();
+            let response = Response::from_parts(parts, body.into());
+            let binary = encoding.is_binary(&response);
+            Ok(ProxyResponse {
+                source,
+                response,
+                binary,
+            })
+        }
+        .boxed()
+    }
+}
+
+/// Inserts `headers` into `map`, preferring their multi-value form when it is present.
+fn insert_headers(
+    map: &mut HeaderMap,
+    headers: Option<HashMap<String, String>>,
+    multi_value_headers: Option<HashMap<String, Vec<String>>>,
+) -> Result<(), http::Error> {
+    match multi_value_headers.filter(|headers| !headers.is_empty()) {
+        Some(headers) => {
+            for (name, values) in headers {
+                let name = HeaderName::from_bytes(name.as_bytes())?;
+                for value in values {
+                    map.append(&name, HeaderValue::from_str(&value)?);
+                }
+            }
+        }
+        None => {
+            for (name, value) in headers.unwrap_or_default() {
+                let name = HeaderName::from_bytes(name.as_bytes())?;
+                map.append(name, HeaderValue::from_str(&value)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Builds the query string of a request, preferring the multi-value form of its parameters
+/// when it is present.
+fn query_string(
+    params: Option<HashMap<String, String>>,
+    multi_value_params: Option<HashMap<String, Vec<String>>>,
+) -> Option<String> {
+    let mut pairs: Vec<(String, String)> = match multi_value_params {
+        Some(params) if !params.is_empty() => params
+            .into_iter()
+            .flat_map(|(name, values)| values.into_iter().map(move |value| (name.clone(), value)))
+            .collect(),
+        _ => params.unwrap_or_default().into_iter().collect(),
+    };
+    if pairs.is_empty() {
+        return None;
+    }
+    // Integrations do not preserve the order of parameters, so they are sorted to give
+    // every request with the same parameters the same `Uri`.
+    pairs.sort();
+    serde_urlencoded::to_string(pairs).ok()
+}
+
+/// Decodes the body of an event, which integrations encode as base64 when it is binary.
+fn decode_body(
+    body: Option<String>,
+    is_base64_encoded: bool,
+) -> Result<Bytes, base64::DecodeError> {
+    match body {
+        Some(body) if is_base64_encoded => Ok(base64::decode(&body)?.into()),
+        Some(body) => Ok(body.into()),
+        None => Ok(Bytes::new()),
+    }
+}
+
+/// Groups the values of `headers` by name. Values that are not valid UTF-8 are converted
+/// lossily.
+fn multi_value_headers(headers: &HeaderMap) -> HashMap<&str, Vec<String>> {
+    let mut map: HashMap<&str, Vec<String>> = HashMap::new();
+    for (name, value) in headers {
+        let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+        map.entry(name.as_str()).or_default().push(value);
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adapter, BodyEncoding, ProxyRequest, ProxyResponse, RequestExt};
+    use crate::{handler_fn, testing, Err, LambdaCtx};
+    use bytes::Bytes;
+    use http::{Request, Response, StatusCode};
+
+    async fn echo(req: Request<Bytes>, ctx: Option<LambdaCtx>) -> Result<Response<Vec<u8>>, Err> {
+        assert_eq!(ctx.as_ref(), req.lambda_context());
+        let res = Response::builder()
+            .status(StatusCode::CREATED)
+            .header("x-path", req.uri().path())
+            .header("set-cookie", "a=1")
+            .header("set-cookie", "b=2")
+            .body(req.body().to_vec())?;
+        Ok(res)
+    }
+
+    #[runtime::test]
+    async fn run_adapter() {
+        let event = include_str!("../../tests/events/apigw-proxy-request.json");
+        let ctx = testing::CtxBuilder::new().build();
+        let res = testing::invoke(adapter(handler_fn(echo)), event.as_bytes(), ctx).await;
+        let res: serde_json::Value = serde_json::from_slice(&res.unwrap()).unwrap();
+
+        assert_eq!(201, res["statusCode"]);
+        assert_eq!("hello", res["body"]);
+        assert_eq!(false, res["isBase64Encoded"]);
+        assert_eq!(
+            serde_json::json!(["/hello/world"]),
+            res["multiValueHeaders"]["x-path"]
+        );
+        assert_eq!(
+            serde_json::json!(["a=1", "b=2"]),
+            res["multiValueHeaders"]["set-cookie"]
+        );
+    }
+
+    #[test]
+    fn binary_response() {
+        let response = Response::new(Bytes::from(&[0xff, 0xfe][..]));
+        let res = ProxyResponse {
+            source: super::Source::ApiGateway,
+            response,
+            binary: false,
+        };
+        let res = serde_json::to_value(res).unwrap();
+        assert_eq!(true, res["isBase64Encoded"]);
+        assert_eq!("//4=", res["body"]);
+    }
+
+    #[test]
+    fn body_encoding() {
+        let encoding = BodyEncoding::default();
+        let is_binary = |encoding: &BodyEncoding, headers: &[(&str, &str)]| {
+            let mut res = Response::builder();
+            for (name, value) in headers {
+                res.header(*name, *value);
+            }
+            encoding.is_binary(&res.body(()).unwrap())
+        };
+
+        assert!(!is_binary(&encoding, &[]));
+        assert!(!is_binary(
+            &encoding,
+            &[("content-type", "text/html; charset=utf-8")]
+        ));
+        assert!(!is_binary(
+            &encoding,
+            &[("content-type", "Application/JSON")]
+        ));
+        assert!(!is_binary(
+            &encoding,
+            &[("content-type", "application/hal+json")]
+        ));
+        assert!(is_binary(&encoding, &[("content-type", "image/png")]));
+        assert!(is_binary(
+            &encoding,
+            &[("content-type", "application/octet-stream")]
+        ));
+        assert!(is_binary(
+            &encoding,
+            &[("content-type", "text/plain"), ("content-encoding", "gzip")]
+        ));
+        assert!(!is_binary(
+            &encoding,
+            &[
+                ("content-type", "text/plain"),
+                ("content-encoding", "identity")
+            ]
+        ));
+
+        let encoding = adapter(handler_fn(echo))
+            .text_content_types(vec!["image/svg+xml"])
+            .encoding;
+        assert!(!is_binary(&encoding, &[("content-type", "image/svg+xml")]));
+        assert!(is_binary(&encoding, &[("content-type", "text/plain")]));
+    }
+
+    #[runtime::test]
+    async fn compressed_response() {
+        async fn gzip(_: Request<Bytes>, _: Option<LambdaCtx>) -> Result<Response<String>, Err> {
+            let res = Response::builder()
+                .header("content-type", "text/plain")
+                .header("content-encoding", "gzip")
+                .body("not really gzip".to_string())?;
+            Ok(res)
+        }
+
+        let event = include_str!("../../tests/events/apigw-proxy-request.json");
+        let ctx = testing::CtxBuilder::new().build();
+        let res = testing::invoke(adapter(handler_fn(gzip)), event.as_bytes(), ctx).await;
+        let res: serde_json::Value = serde_json::from_slice(&res.unwrap()).unwrap();
+        assert_eq!(true, res["isBase64Encoded"]);
+        assert_eq!(base64::encode("not really gzip"), res["body"]);
+    }
+
+    #[test]
+    fn malformed_request() {
+        let event = r#"{"httpMethod":"GET","path":"not a path","requestContext":{}}"#;
+        assert!(serde_json::from_str::<ProxyRequest>(event).is_err());
+    }
+}
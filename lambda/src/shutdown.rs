@@ -0,0 +1,83 @@
+use futures::{
+    channel::oneshot,
+    prelude::*,
+    task::{Context, Poll},
+};
+use signal_hook::{
+    consts::SIGTERM,
+    iterator::{Handle, Signals},
+    low_level,
+};
+use std::{
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Once,
+    },
+    thread,
+};
+
+/// The number of [`Sigterm`]s that exist in the process.
+static LISTENERS: AtomicUsize = AtomicUsize::new(0);
+/// Registers the action that terminates the process on SIGTERM once no one listens for it.
+static DEFAULT_ACTION: Once = Once::new();
+
+/// A future that resolves when the process receives SIGTERM, which Lambda sends before it
+/// shuts down the execution environment.
+///
+/// While the future exists, SIGTERM no longer terminates the process. Dropping it stops
+/// listening for the signal, and once no other `Sigterm` exists, SIGTERM terminates the
+/// process again.
+pub(crate) struct Sigterm {
+    received: oneshot::Receiver<()>,
+    handle: Handle,
+}
+
+impl Sigterm {
+    pub(crate) fn new() -> io::Result<Self> {
+        let mut registered = Ok(());
+        DEFAULT_ACTION.call_once(|| {
+            // Safety: the action only loads an atomic and calls `emulate_default_handler`,
+            // both of which are async-signal-safe.
+            let action = || {
+                if LISTENERS.load(Ordering::SeqCst) == 0 {
+                    let _ = low_level::emulate_default_handler(SIGTERM);
+                }
+            };
+            registered = unsafe { low_level::register(SIGTERM, action) }.map(drop);
+        });
+        registered?;
+
+        let mut signals = Signals::new([SIGTERM])?;
+        LISTENERS.fetch_add(1, Ordering::SeqCst);
+        let handle = signals.handle();
+        let (tx, received) = oneshot::channel();
+        thread::spawn(move || {
+            // The iterator ends without a signal once the `Handle` is closed.
+            if signals.forever().next().is_some() {
+                let _ = tx.send(());
+            }
+        });
+        Ok(Self { received, handle })
+    }
+}
+
+impl Future for Sigterm {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        match self.received.poll_unpin(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(()),
+            // The listening thread is gone, so the signal can never be observed.
+            Poll::Ready(Err(_)) | Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for Sigterm {
+    fn drop(&mut self) {
+        self.handle.close();
+        LISTENERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,578 @@
+use crate::{client::EventClient, extension::Extension, Err};
+use bytes::Bytes;
+use futures::{
+    channel::mpsc::{self, Receiver, Sender},
+    prelude::*,
+    task::{Context, Poll},
+};
+use futures01::{sync::oneshot, Future as Future01, Stream as Stream01};
+use http::{Method, Request, Response, StatusCode, Uri};
+use hyper::{service::service_fn, Body, Server};
+use serde::{Deserialize, Serialize};
+use std::{
+    net::SocketAddr,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+};
+
+/// The schema version of the records requested from the Telemetry API.
+const SCHEMA_VERSION: &str = "2022-12-13";
+
+/// The number of batches the listener holds before asking Lambda to deliver them again.
+const BATCH_CAPACITY: usize = 16;
+
+/// The sending half of the channel through which the listener yields batches.
+type BatchSender = Sender<Result<Vec<TelemetryEvent>, Err>>;
+
+/// The kinds of records a subscriber can receive.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TelemetryType {
+    /// Records about the lifecycle of the execution environment and its invocations.
+    Platform,
+    /// Logs written by the function.
+    Function,
+    /// Logs written by extensions.
+    Extension,
+}
+
+/// How Lambda batches records before delivering them to the listener. A batch is sent as
+/// soon as any of the limits is reached.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Buffering {
+    /// The maximum number of records in a batch.
+    pub max_items: u32,
+    /// The maximum size of a batch in bytes.
+    pub max_bytes: u32,
+    /// The maximum time to buffer a batch for, in milliseconds.
+    pub timeout_ms: u32,
+}
+
+impl Default for Buffering {
+    fn default() -> Self {
+        Self {
+            max_items: 1000,
+            max_bytes: 256 * 1024,
+            timeout_ms: 1000,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscribeRequest<'a> {
+    schema_version: &'a str,
+    types: &'a [TelemetryType],
+    buffering: Buffering,
+    destination: Destination,
+}
+
+#[derive(Serialize)]
+struct Destination {
+    protocol: &'static str,
+    #[serde(rename = "URI")]
+    uri: String,
+}
+
+/// A record delivered by the [Telemetry
+/// API](https://docs.aws.amazon.com/lambda/latest/dg/telemetry-api.html).
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "RawEvent")]
+pub struct TelemetryEvent {
+    /// When the record was generated, as an ISO 8601 timestamp.
+    pub time: String,
+    /// The content of the record.
+    pub record: TelemetryRecord,
+}
+
+/// A record as delivered, before its content is parsed according to its type.
+#[derive(Deserialize)]
+struct RawEvent {
+    time: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    record: serde_json::Value,
+}
+
+impl From<RawEvent> for TelemetryEvent {
+    fn from(raw: RawEvent) -> Self {
+        let RawEvent { time, kind, record } = raw;
+        let tagged = serde_json::json!({ "type": kind, "record": record });
+        // Records of unknown types, or that do not match their type, are kept as they are.
+        let record =
+            serde_json::from_value(tagged).unwrap_or(TelemetryRecord::Unknown { kind, record });
+        Self { time, record }
+    }
+}
+
+/// The content of a [`TelemetryEvent`], identified by its `type`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "record")]
+pub enum TelemetryRecord {
+    /// A line logged by the function, which is a string or, for functions that log in
+    /// JSON format, an object.
+    #[serde(rename = "function")]
+    Function(serde_json::Value),
+    /// A line logged by an extension.
+    #[serde(rename = "extension")]
+    Extension(serde_json::Value),
+    /// The initialization phase started.
+    #[serde(rename = "platform.initStart", rename_all = "camelCase")]
+    PlatformInitStart {
+        /// How the execution environment was initialized, such as `on-demand`.
+        initialization_type: String,
+        /// The phase being initialized, `init` or `invoke`.
+        phase: String,
+        /// The version of the managed runtime, if any.
+        runtime_version: Option<String>,
+    },
+    /// The runtime finished initializing.
+    #[serde(rename = "platform.initRuntimeDone", rename_all = "camelCase")]
+    PlatformInitRuntimeDone {
+        /// How the execution environment was initialized.
+        initialization_type: String,
+        /// Whether initialization succeeded, such as `success` or `error`.
+        status: String,
+        /// The type of the error, if initialization failed.
+        error_type: Option<String>,
+    },
+    /// Metrics about the initialization phase.
+    #[serde(rename = "platform.initReport", rename_all = "camelCase")]
+    PlatformInitReport {
+        /// How the execution environment was initialized.
+        initialization_type: String,
+        /// The phase that was initialized.
+        phase: String,
+        /// The metrics of the phase.
+        metrics: InitReportMetrics,
+    },
+    /// An invocation started.
+    #[serde(rename = "platform.start", rename_all = "camelCase")]
+    PlatformStart {
+        /// The AWS request ID of the invocation.
+        request_id: String,
+        /// The version of the function being invoked.
+        version: Option<String>,
+        /// The trace context of the invocation.
+        tracing: Option<TraceContext>,
+    },
+    /// The runtime finished processing an invocation.
+    #[serde(rename = "platform.runtimeDone", rename_all = "camelCase")]
+    PlatformRuntimeDone {
+        /// The AWS request ID of the invocation.
+        request_id: String,
+        /// Whether the invocation succeeded, such as `success`, `error` or `timeout`.
+        status: String,
+        /// The type of the error, if the invocation failed.
+        error_type: Option<String>,
+        /// The metrics of the invocation.
+        metrics: Option<RuntimeDoneMetrics>,
+    },
+    /// Metrics about a completed invocation.
+    #[serde(rename = "platform.report", rename_all = "camelCase")]
+    PlatformReport {
+        /// The AWS request ID of the invocation.
+        request_id: String,
+        /// Whether the invocation succeeded.
+        status: String,
+        /// The type of the error, if the invocation failed.
+        error_type: Option<String>,
+        /// The metrics of the invocation.
+        metrics: ReportMetrics,
+    },
+    /// An extension registered.
+    #[serde(rename = "platform.extension")]
+    PlatformExtension {
+        /// The name of the extension.
+        name: String,
+        /// The state of the extension, such as `Ready`.
+        state: String,
+        /// The lifecycle events the extension registered for.
+        events: Vec<String>,
+    },
+    /// An extension subscribed to the Telemetry API.
+    #[serde(rename = "platform.telemetrySubscription")]
+    PlatformTelemetrySubscription {
+        /// The name of the extension.
+        name: String,
+        /// The state of the subscription, such as `Subscribed`.
+        state: String,
+        /// The kinds of records subscribed to.
+        types: Vec<String>,
+    },
+    /// Lambda dropped records because the subscriber could not keep up.
+    #[serde(rename = "platform.logsDropped", rename_all = "camelCase")]
+    PlatformLogsDropped {
+        /// Why the records were dropped.
+        reason: String,
+        /// The number of records dropped.
+        dropped_records: u64,
+        /// The number of bytes dropped.
+        dropped_bytes: u64,
+    },
+    /// A record of a type this crate does not know about.
+    #[serde(skip_deserializing)]
+    Unknown {
+        /// The type of the record.
+        kind: String,
+        /// The content of the record.
+        record: serde_json::Value,
+    },
+}
+
+/// The trace context of an invocation.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceContext {
+    /// The ID of the span, if any.
+    pub span_id: Option<String>,
+    /// The kind of tracing header, such as `X-Amzn-Trace-Id`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The value of the tracing header.
+    pub value: String,
+}
+
+/// Metrics of a `platform.initReport` record.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InitReportMetrics {
+    /// The duration of the phase in milliseconds.
+    pub duration_ms: f64,
+}
+
+/// Metrics of a `platform.runtimeDone` record.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeDoneMetrics {
+    /// The time the runtime spent on the invocation in milliseconds.
+    pub duration_ms: f64,
+    /// The size of the response in bytes.
+    pub produced_bytes: Option<u64>,
+}
+
+/// Metrics of a `platform.report` record.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportMetrics {
+    /// The duration of the invocation in milliseconds.
+    pub duration_ms: f64,
+    /// The billed duration of the invocation in milliseconds.
+    pub billed_duration_ms: u64,
+    /// The memory configured for the function in MB.
+    #[serde(rename = "memorySizeMB")]
+    pub memory_size_mb: u64,
+    /// The maximum memory used by the function in MB.
+    #[serde(rename = "maxMemoryUsedMB")]
+    pub max_memory_used_mb: u64,
+    /// The duration of the initialization phase in milliseconds, for the first invocation
+    /// of an execution environment.
+    pub init_duration_ms: Option<f64>,
+}
+
+/// A local HTTP listener that receives batches of records from the [Telemetry
+/// API](https://docs.aws.amazon.com/lambda/latest/dg/telemetry-api.html).
+///
+/// The listener is a `Stream` of the batches it receives. Batches that cannot be parsed are
+/// rejected, and Lambda delivers them again, as are batches received while the listener
+/// already holds as many as it can buffer. The listener stops when it is dropped, and if it
+/// fails, the stream yields its error and ends.
+///
+/// # Example
+/// ```no_run
+/// #![feature(async_await)]
+///
+/// use futures::prelude::*;
+/// use lambda::{
+///     extension::{EventType, Extension},
+///     telemetry::{Buffering, TelemetryListener, TelemetryType},
+/// };
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// #[runtime::main]
+/// async fn main() -> Result<(), Err> {
+///     let extension = Extension::register("log-shipper", &[EventType::Shutdown]).await?;
+///     let mut listener = TelemetryListener::bind(8080)?;
+///     let types = [TelemetryType::Platform, TelemetryType::Function];
+///     listener.subscribe(&extension, &types, Buffering::default()).await?;
+///
+///     runtime::spawn(async move {
+///         while let Some(Ok(batch)) = listener.next().await {
+///             for event in batch {
+///                 println!("{:?}", event.record);
+///             }
+///         }
+///     });
+///     let _ = extension.events().into_future().await;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct TelemetryListener {
+    addr: SocketAddr,
+    batches: Receiver<Result<Vec<TelemetryEvent>, Err>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl TelemetryListener {
+    /// Starts a listener on `port` of every interface of the execution environment.
+    ///
+    /// # Errors
+    /// Returns an error if the listener could not bind to `port`.
+    pub fn bind(port: u16) -> Result<Self, Err> {
+        Self::bind_addr(([0, 0, 0, 0], port).into())
+    }
+
+    /// Starts a listener on `addr`.
+    ///
+    /// # Errors
+    /// Returns an error if the listener could not bind to `addr`.
+    pub fn bind_addr(addr: SocketAddr) -> Result<Self, Err> {
+        let (tx, batches) = mpsc::channel(BATCH_CAPACITY);
+        let mut failed = tx.clone();
+        // Every clone of a sender can always send one batch, so requests share a single one.
+        let tx = Arc::new(Mutex::new(tx));
+        let server = Server::try_bind(&addr)?.serve(move || {
+            let tx = Arc::clone(&tx);
+            service_fn(move |req| receive(&tx, req))
+        });
+        let addr = server.local_addr();
+
+        let (shutdown, signal) = oneshot::channel();
+        let server = server.with_graceful_shutdown(signal).map_err(move |err| {
+            let _ = failed.try_send(Err(err.into()));
+        });
+        thread::spawn(move || hyper::rt::run(server));
+
+        Ok(Self {
+            addr,
+            batches,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// The address the listener is bound to.
+    #[must_use]
+    pub const fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The URI at which Lambda can reach the listener from the Telemetry API.
+    #[must_use]
+    pub fn uri(&self) -> String {
+        format!("http://sandbox.localdomain:{}", self.addr.port())
+    }
+
+    /// Subscribes `extension` to the `types` of records, delivered to this listener in
+    /// batches according to `buffering`.
+    ///
+    /// # Errors
+    /// Returns an error if the Telemetry API rejects the subscription.
+    pub async fn subscribe<C>(
+        &self,
+        extension: &Extension<C>,
+        types: &[TelemetryType],
+        buffering: Buffering,
+    ) -> Result<(), Err>
+    where
+        C: for<'a> EventClient<'a>,
+    {
+        let body = SubscribeRequest {
+            schema_version: SCHEMA_VERSION,
+            types,
+            buffering,
+            destination: Destination {
+                protocol: "HTTP",
+                uri: self.uri(),
+            },
+        };
+        let req = Request::builder()
+            .uri(Uri::from_static("/2022-07-01/telemetry"))
+            .method(Method::PUT)
+            .body(Bytes::from(serde_json::to_vec(&body)?))?;
+
+        extension.call(req).await?;
+        Ok(())
+    }
+}
+
+impl Stream for TelemetryListener {
+    type Item = Result<Vec<TelemetryEvent>, Err>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.batches.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for TelemetryListener {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+fn receive(
+    tx: &Arc<Mutex<BatchSender>>,
+    req: Request<Body>,
+) -> impl Future01<Item = Response<Body>, Error = hyper::Error> {
+    let tx = Arc::clone(tx);
+    req.into_body().concat2().map(move |body| {
+        let status = serde_json::from_slice(&body).map_or(StatusCode::BAD_REQUEST, |batch| {
+            let mut tx = tx.lock().unwrap_or_else(PoisonError::into_inner);
+            match tx.try_send(Ok(batch)) {
+                Ok(()) => StatusCode::OK,
+                // The listener is full or was dropped, so Lambda has to retry the batch.
+                Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+            }
+        });
+        let mut res = Response::new(Body::empty());
+        *res.status_mut() = status;
+        res
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Buffering, TelemetryEvent, TelemetryListener, TelemetryRecord, TelemetryType,
+        BATCH_CAPACITY,
+    };
+    use crate::{
+        client::{Client, EventClient},
+        extension::Extension,
+        Err,
+    };
+    use bytes::Bytes;
+    use futures::{
+        future::{self, Ready},
+        prelude::*,
+    };
+    use http::{Method, Request, Response, StatusCode};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn deserialize_records() {
+        let batch = r#"[
+            {"time":"2022-10-12T00:00:00.000Z","type":"platform.start","record":{"requestId":"6d68ca91-49c9-448d-89b8-7ca3e6dc66aa","version":"$LATEST","tracing":{"spanId":"54565fb41ac79632","type":"X-Amzn-Trace-Id","value":"Root=1-62e900b2-710d76f009d6e7785905449a;Parent=0efbd19962d95b05;Sampled=1"}}},
+            {"time":"2022-10-12T00:00:00.001Z","type":"function","record":"Hello, world!\n"},
+            {"time":"2022-10-12T00:00:00.002Z","type":"platform.report","record":{"requestId":"6d68ca91-49c9-448d-89b8-7ca3e6dc66aa","status":"success","metrics":{"durationMs":1.5,"billedDurationMs":2,"memorySizeMB":128,"maxMemoryUsedMB":20,"initDurationMs":25.3}}},
+            {"time":"2022-10-12T00:00:00.003Z","type":"platform.restoreStart","record":{}}
+        ]"#;
+        let batch: Vec<TelemetryEvent> = serde_json::from_str(batch).unwrap();
+        match &batch[0].record {
+            TelemetryRecord::PlatformStart {
+                request_id,
+                tracing,
+                ..
+            } => {
+                assert_eq!("6d68ca91-49c9-448d-89b8-7ca3e6dc66aa", request_id);
+                assert_eq!("X-Amzn-Trace-Id", tracing.as_ref().unwrap().kind);
+            }
+            record => panic!("Expected platform.start, got {:?}", record),
+        }
+        assert_eq!(
+            TelemetryRecord::Function(serde_json::Value::from("Hello, world!\n")),
+            batch[1].record
+        );
+        match &batch[2].record {
+            TelemetryRecord::PlatformReport { metrics, .. } => {
+                assert_eq!(2, metrics.billed_duration_ms);
+                assert_eq!(128, metrics.memory_size_mb);
+            }
+            record => panic!("Expected platform.report, got {:?}", record),
+        }
+        match &batch[3].record {
+            TelemetryRecord::Unknown { kind, .. } => assert_eq!("platform.restoreStart", kind),
+            record => panic!("Expected an unknown record, got {:?}", record),
+        }
+    }
+
+    /// An `EventClient` that accepts every request and records it.
+    #[derive(Default, Clone)]
+    struct FakeClient {
+        requests: Arc<Mutex<Vec<Request<Bytes>>>>,
+    }
+
+    impl<'a> EventClient<'a> for FakeClient {
+        type Fut = Ready<Result<Response<Bytes>, Err>>;
+
+        fn call(&self, req: Request<Bytes>) -> Self::Fut {
+            self.requests.lock().unwrap().push(req);
+            let res = Response::builder()
+                .header("lambda-extension-identifier", "ext-id")
+                .body(Bytes::from(
+                    r#"{"functionName":"test","functionVersion":"$LATEST","handler":"bootstrap"}"#,
+                ))
+                .unwrap();
+            future::ready(Ok(res))
+        }
+    }
+
+    #[runtime::test]
+    async fn full_listener() -> Result<(), Err> {
+        let listener = TelemetryListener::bind_addr(([127, 0, 0, 1], 0).into())?;
+        let lambda = Client::new(format!("http://{}", listener.addr()).parse()?);
+        let batch = r#"[{"time":"2022-10-12T00:00:00.000Z","type":"function","record":"log"}]"#;
+        let mut delivered = 0;
+        loop {
+            let req = Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .body(Bytes::from(batch))?;
+            let res = lambda.call(req).await?;
+            if !res.status().is_success() {
+                assert_eq!(StatusCode::SERVICE_UNAVAILABLE, res.status());
+                break;
+            }
+            delivered += 1;
+            assert!(delivered <= BATCH_CAPACITY + 1);
+        }
+        Ok(())
+    }
+
+    #[runtime::test]
+    async fn subscribe_and_receive() -> Result<(), Err> {
+        let client = FakeClient::default();
+        let extension = Extension::register_with_client(client.clone(), "ext", &[]).await?;
+        let mut listener = TelemetryListener::bind_addr(([127, 0, 0, 1], 0).into())?;
+        let types = [TelemetryType::Platform];
+        listener
+            .subscribe(&extension, &types, Buffering::default())
+            .await?;
+
+        {
+            let requests = client.requests.lock().unwrap();
+            let req = &requests[1];
+            assert_eq!(&Method::PUT, req.method());
+            assert_eq!("/2022-07-01/telemetry", req.uri().path());
+            assert_eq!("ext-id", req.headers()["lambda-extension-identifier"]);
+            let body: serde_json::Value = serde_json::from_slice(req.body())?;
+            assert_eq!(serde_json::json!(["platform"]), body["types"]);
+            assert_eq!(
+                format!("http://sandbox.localdomain:{}", listener.addr().port()),
+                body["destination"]["URI"]
+            );
+        }
+
+        let lambda = Client::new(format!("http://{}", listener.addr()).parse()?);
+        let batch =
+            r#"[{"time":"2022-10-12T00:00:00.000Z","type":"extension","record":"flushed"}]"#;
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/")
+            .body(Bytes::from(batch))?;
+        let res = lambda.call(req).await?;
+        assert!(res.status().is_success());
+
+        let batch = listener.next().await.unwrap()?;
+        assert_eq!(
+            TelemetryRecord::Extension(serde_json::Value::from("flushed")),
+            batch[0].record
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{
+    codec::Codec, error_hook::ErrorReport, ClientContext, CognitoIdentity, Config, Handler,
+    LambdaCtx, Runtime,
+};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Returns a request ID in the format used by Lambda, unique within the process.
+pub(crate) fn request_id() -> String {
+    format!(
+        "00000000-0000-4000-8000-{:012}",
+        NEXT_ID.fetch_add(1, Ordering::SeqCst)
+    )
+}
+
+/// Returns the deadline, in Unix time milliseconds, `timeout` from now.
+pub(crate) fn deadline_in(timeout: Duration) -> u64 {
+    let deadline = SystemTime::now() + timeout;
+    let deadline = deadline.duration_since(UNIX_EPOCH).unwrap_or_default();
+    deadline.as_secs() * 1000 + u64::from(deadline.subsec_millis())
+}
+
+/// Builds a [`LambdaCtx`] resembling the one the runtime passes to a handler.
+///
+/// Unless overridden, the context has a unique request ID, a deadline three seconds after
+/// it is built, the ARN of a function named `test` and the matching environment
+/// configuration of a function with 128 MB of memory.
+///
+/// # Example
+/// ```
+/// use lambda::testing::CtxBuilder;
+/// use std::time::Duration;
+///
+/// let ctx = CtxBuilder::new()
+///     .timeout(Duration::from_secs(30))
+///     .trace_id("Root=1-5759e988-bd862e3fe1be46a994272793")
+///     .build();
+/// assert!(ctx.remaining_time() > Duration::from_secs(29));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CtxBuilder {
+    ctx: LambdaCtx,
+    timeout: Option<Duration>,
+}
+
+impl Default for CtxBuilder {
+    fn default() -> Self {
+        let env_config = Config {
+            endpoint: String::from("127.0.0.1:9001"),
+            function_name: String::from("test"),
+            memory: 128,
+            version: String::from("$LATEST"),
+            log_stream: String::from("2019/01/01/[$LATEST]00000000000000000000000000000000"),
+            log_group: String::from("/aws/lambda/test"),
+        };
+        let ctx = LambdaCtx {
+            id: request_id(),
+            invoked_function_arn: String::from(
+                "arn:aws:lambda:us-east-1:123456789012:function:test",
+            ),
+            env_config,
+            ..LambdaCtx::default()
+        };
+        Self {
+            ctx,
+            timeout: Some(Duration::from_secs(3)),
+        }
+    }
+}
+
+impl CtxBuilder {
+    /// Returns a builder with the default values.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the request ID of the invocation.
+    #[must_use]
+    pub fn request_id<S: Into<String>>(mut self, id: S) -> Self {
+        self.ctx.id = id.into();
+        self
+    }
+
+    /// Sets the deadline of the invocation to `timeout` after the context is built.
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the deadline of the invocation, in Unix time milliseconds.
+    #[must_use]
+    pub const fn deadline(mut self, deadline: u64) -> Self {
+        self.ctx.deadline = deadline;
+        self.timeout = None;
+        self
+    }
+
+    /// Sets the ARN of the function being invoked.
+    #[must_use]
+    pub fn function_arn<S: Into<String>>(mut self, arn: S) -> Self {
+        self.ctx.invoked_function_arn = arn.into();
+        self
+    }
+
+    /// Sets the X-Ray trace ID of the invocation.
+    #[must_use]
+    pub fn trace_id<S: Into<String>>(mut self, trace_id: S) -> Self {
+        self.ctx.xray_trace_id = Some(trace_id.into());
+        self
+    }
+
+    /// Sets the client context sent by the AWS Mobile SDK.
+    #[must_use]
+    pub fn client_context(mut self, client_context: ClientContext) -> Self {
+        self.ctx.client_context = Some(client_context);
+        self
+    }
+
+    /// Sets the Cognito identity that made the invocation.
+    #[must_use]
+    pub fn identity(mut self, identity: CognitoIdentity) -> Self {
+        self.ctx.identity = Some(identity);
+        self
+    }
+
+    /// Sets the configuration read from the function's environment variables.
+    #[must_use]
+    pub fn env_config(mut self, env_config: Config) -> Self {
+        self.ctx.env_config = env_config;
+        self
+    }
+
+    /// Returns the context.
+    #[must_use]
+    pub fn build(self) -> LambdaCtx {
+        let mut ctx = self.ctx;
+        if let Some(timeout) = self.timeout {
+            ctx.deadline = deadline_in(timeout);
+        }
+        ctx
+    }
+}
+
+/// Invokes `handler` with the raw `event` as `runtime` would, returning the encoded response
+/// or the [`ErrorReport`] that would be sent to Lambda.
+///
+/// Malformed events, handler errors and panics are all reported. Deadlines are only
+/// enforced by `runtime`; use [`invoke`] for the default settings.
+///
+/// # Errors
+/// Returns the report of a failed invocation.
+pub async fn invoke_with<Format, Function, Event, Output>(
+    runtime: &Runtime<Format>,
+    mut handler: Function,
+    event: &[u8],
+    ctx: LambdaCtx,
+) -> Result<Bytes, ErrorReport>
+where
+    Function: Handler<Event, Output>,
+    Format: Codec<Event, Output>,
+{
+    runtime.invoke(&mut handler, event, &ctx).await
+}
+
+/// Invokes `handler` with the raw JSON `event` using a [`Runtime`] with the default settings.
+///
+/// See [`invoke_with`].
+///
+/// # Example
+/// ```
+/// #![feature(async_await)]
+///
+/// use lambda::{handler_fn, testing, LambdaCtx};
+/// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// async fn func(event: String, _ctx: Option<LambdaCtx>) -> Result<String, Err> {
+///     Ok(event.to_uppercase())
+/// }
+///
+/// #[runtime::main]
+/// async fn main() {
+///     let ctx = testing::CtxBuilder::new().build();
+///     let res = testing::invoke(handler_fn(func), br#""hello""#, ctx).await;
+///     assert_eq!(&res.unwrap()[..], br#""HELLO""#);
+/// }
+/// ```
+///
+/// # Errors
+/// Returns the report of a failed invocation.
+pub async fn invoke<Function, Event, Output>(
+    handler: Function,
+    event: &[u8],
+    ctx: LambdaCtx,
+) -> Result<Bytes, ErrorReport>
+where
+    Function: Handler<Event, Output>,
+    Event: for<'de> Deserialize<'de>,
+    Output: Serialize,
+{
+    invoke_with(&Runtime::new(), handler, event, ctx).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{invoke, invoke_with, CtxBuilder};
+    use crate::{handler_fn, Err, LambdaCtx, Runtime};
+    use std::time::Duration;
+
+    async fn func(event: String, ctx: Option<LambdaCtx>) -> Result<String, Err> {
+        let ctx = ctx.unwrap();
+        match event.as_str() {
+            "name" => Ok(ctx.env_config.function_name),
+            "panic" => panic!("unexpected event"),
+            "sleep" => {
+                runtime::time::Delay::new(Duration::from_secs(10)).await;
+                Ok(event)
+            }
+            _ => Err("failed".into()),
+        }
+    }
+
+    #[test]
+    fn build_ctx() {
+        let first = CtxBuilder::new().build();
+        let second = CtxBuilder::new().timeout(Duration::from_secs(60)).build();
+        assert_ne!(first.id, second.id);
+        assert!(first.remaining_time() <= Duration::from_secs(3));
+        assert!(second.remaining_time() > Duration::from_secs(59));
+        assert_eq!(128, first.env_config.memory);
+
+        let ctx = CtxBuilder::new().deadline(1_542_409_706_888).build();
+        assert_eq!(1_542_409_706_888, ctx.deadline);
+    }
+
+    #[runtime::test]
+    async fn invoke_outcomes() {
+        let ctx = CtxBuilder::new().build();
+        let res = invoke(handler_fn(func), br#""name""#, ctx.clone()).await;
+        assert_eq!(&res.unwrap()[..], br#""test""#);
+
+        let report = invoke(handler_fn(func), br#""fail""#, ctx.clone()).await;
+        assert_eq!("failed", report.unwrap_err().err);
+
+        let report = invoke(handler_fn(func), br#""panic""#, ctx.clone()).await;
+        assert_eq!("Panic", report.unwrap_err().name);
+
+        let report = invoke(handler_fn(func), b"not json", ctx).await;
+        assert!(report.is_err());
+    }
+
+    #[runtime::test]
+    async fn invoke_with_deadline() {
+        let runtime = Runtime::new().enforce_deadline(Duration::from_millis(100));
+        let ctx = CtxBuilder::new()
+            .timeout(Duration::from_millis(300))
+            .build();
+        let report = invoke_with(&runtime, handler_fn(func), br#""sleep""#, ctx).await;
+        assert_eq!("TimeoutError", report.unwrap_err().name);
+    }
+}
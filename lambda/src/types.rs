@@ -1,8 +1,13 @@
 use crate::{err_fmt, Config};
 use headers::{Header, HeaderMap, HeaderMapExt, HeaderName, HeaderValue};
 use lazy_static::lazy_static;
+use runtime::time::Delay;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, convert::TryFrom};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 lazy_static! {
     static ref AWS_REQUEST_ID: HeaderName =
@@ -129,8 +134,10 @@ pub struct ClientContext {
     /// Information about the mobile application invoking the function.
     pub client: ClientApplication,
     /// Custom properties attached to the mobile event context.
+    #[serde(default)]
     pub custom: HashMap<String, String>,
     /// Environment settings from the mobile client.
+    #[serde(default, rename = "env")]
     pub environment: HashMap<String, String>,
 }
 
@@ -154,11 +161,54 @@ pub struct ClientApplication {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct CognitoIdentity {
     /// The unique identity id for the Cognito credentials invoking the function.
+    #[serde(rename = "cognitoIdentityId")]
     pub identity_id: String,
     /// The identity pool id the caller is "registered" with.
+    #[serde(rename = "cognitoIdentityPoolId")]
     pub identity_pool_id: String,
 }
 
+/// An error returned when a header sent by the Runtime APIs holds a malformed value.
+#[derive(Debug)]
+pub struct InvalidHeader {
+    name: &'static HeaderName,
+    source: serde_json::Error,
+}
+
+impl InvalidHeader {
+    /// The name of the malformed header.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+impl std::fmt::Display for InvalidHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid {} header: {}", self.name, self.source)
+    }
+}
+
+impl std::error::Error for InvalidHeader {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Decodes a header holding a JSON document, which may additionally be base64-encoded.
+fn decode_json<T>(name: &'static HeaderName, value: &str) -> Result<T, InvalidHeader>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let res = if value.trim_start().starts_with('{') {
+        serde_json::from_str(value)
+    } else {
+        let json = base64::decode(value).unwrap_or_else(|_| value.as_bytes().to_vec());
+        serde_json::from_slice(&json)
+    };
+    res.map_err(|source| InvalidHeader { name, source })
+}
+
 /// The Lambda function execution context. The values in this struct
 /// are populated using the [Lambda environment variables](https://docs.aws.amazon.com/lambda/latest/dg/current-supported-versions.html)
 /// and the headers returned by the poll request to the Runtime APIs.
@@ -185,6 +235,62 @@ pub struct LambdaCtx {
     pub env_config: Config,
 }
 
+impl LambdaCtx {
+    /// Returns the point in time at which the current invocation times out.
+    #[must_use]
+    pub fn deadline_system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.deadline)
+    }
+
+    /// Returns the [`Instant`] at which the current invocation times out, for use with
+    /// monotonic timers.
+    #[must_use]
+    pub fn deadline_instant(&self) -> Instant {
+        Instant::now() + self.remaining_time()
+    }
+
+    /// Returns the time left before the current invocation times out, or zero if the
+    /// deadline has passed.
+    #[must_use]
+    pub fn remaining_time(&self) -> Duration {
+        self.deadline_system_time()
+            .duration_since(SystemTime::now())
+            .unwrap_or_default()
+    }
+
+    /// Returns a future that resolves `margin` before the current invocation times out, or
+    /// immediately if that point has passed.
+    ///
+    /// Racing work against this future allows a handler to stop gracefully and return a
+    /// partial result instead of being stopped by Lambda.
+    ///
+    /// # Example
+    /// ```
+    /// #![feature(async_await)]
+    ///
+    /// use futures::future::{self, Either};
+    /// use lambda::LambdaCtx;
+    /// use std::time::Duration;
+    /// type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+    ///
+    /// async fn func(event: String, ctx: Option<LambdaCtx>) -> Result<String, Err> {
+    ///     let ctx = ctx.unwrap_or_default();
+    ///     let work = Box::pin(async { event });
+    ///     match future::select(work, ctx.deadline_reached(Duration::from_millis(500))).await {
+    ///         Either::Left((res, _)) => Ok(res),
+    ///         Either::Right(_) => Ok(String::from("timed out")),
+    ///     }
+    /// }
+    /// ```
+    pub fn deadline_reached(&self, margin: Duration) -> Delay {
+        let left = self
+            .remaining_time()
+            .checked_sub(margin)
+            .unwrap_or_default();
+        Delay::new(left)
+    }
+}
+
 impl TryFrom<HeaderMap<HeaderValue>> for LambdaCtx {
     type Error = crate::Err;
 
@@ -199,12 +305,22 @@ impl TryFrom<HeaderMap<HeaderValue>> for LambdaCtx {
             .typed_get::<InvocationDeadline>()
             .ok_or(err_fmt!("FunctionArn not found"))?;
         let xray = value.typed_get::<XRayTraceId>();
+        let client_context = value
+            .typed_get::<MobileClientContext>()
+            .map(|v| decode_json(&AWS_MOBILE_CLIENT_CONTEXT, &v.0))
+            .transpose()?;
+        let identity = value
+            .typed_get::<MobileClientIdentity>()
+            .map(|v| decode_json(&AWS_MOBILE_CLIENT_IDENTITY, &v.0))
+            .transpose()?;
 
         let ctx = LambdaCtx {
             id: request_id.0,
             deadline: deadline.0,
             invoked_function_arn: function_arn.0,
             xray_trace_id: xray.map(|v| v.0),
+            client_context,
+            identity,
             ..Default::default()
         };
         Ok(ctx)
@@ -215,13 +331,18 @@ impl TryFrom<HeaderMap<HeaderValue>> for LambdaCtx {
 #[allow(dead_code)]
 mod tests {
     use crate::types::{
-        ClientApplication, ClientContext, CognitoIdentity, FunctionArn, InvocationDeadline,
-        MobileClientContext, MobileClientIdentity, RequestId, XRayTraceId,
+        ClientApplication, ClientContext, CognitoIdentity, FunctionArn, InvalidHeader,
+        InvocationDeadline, LambdaCtx, MobileClientContext, MobileClientIdentity, RequestId,
+        XRayTraceId,
     };
     use bytes::Bytes;
     use headers::{HeaderMap, HeaderMapExt};
     use http::Response;
     use proptest::{collection, option, prelude::*, strategy::Strategy, string::string_regex};
+    use std::{
+        convert::TryFrom,
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    };
 
     fn gen_request_id() -> impl Strategy<Value = RequestId> {
         let expr = "[0-9A-F]{8}-[0-9A-F]{4}-4[0-9A-F]{3}-[89AB][0-9A-F]{3}-[0-9A-F]{12}";
@@ -374,4 +495,97 @@ mod tests {
             prop_assert_eq!(headers.typed_get::<MobileClientIdentity>(), Some(req));
         });
     }
+
+    #[test]
+    fn remaining_time() {
+        let ctx = LambdaCtx::default();
+        assert_eq!(Duration::from_secs(0), ctx.remaining_time());
+
+        let deadline = SystemTime::now() + Duration::from_secs(60);
+        let ctx = LambdaCtx {
+            deadline: deadline.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+            ..Default::default()
+        };
+        assert!(ctx.remaining_time() <= Duration::from_secs(60));
+        assert!(ctx.remaining_time() > Duration::from_secs(50));
+        assert!(ctx.deadline_instant() > Instant::now() + Duration::from_secs(50));
+    }
+
+    #[runtime::test]
+    async fn deadline_reached() {
+        let deadline = SystemTime::now() + Duration::from_millis(100);
+        let ctx = LambdaCtx {
+            deadline: deadline.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        ctx.deadline_reached(Duration::from_millis(50)).await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+        assert!(SystemTime::now() < ctx.deadline_system_time());
+    }
+
+    fn gen_mandatory_headers() -> impl Strategy<Value = HeaderMap> {
+        let mandatory = (
+            gen_request_id(),
+            gen_invocation_deadline(),
+            gen_function_arn(),
+        );
+        mandatory.prop_map(|(id, deadline, arn)| {
+            let mut map = HeaderMap::new();
+            map.typed_insert(id);
+            map.typed_insert(deadline);
+            map.typed_insert(arn);
+            map
+        })
+    }
+
+    #[test]
+    fn ctx_mobile_headers() {
+        let mobile = (
+            gen_client_context_struct(),
+            gen_client_identity_struct(),
+            any::<bool>(),
+        );
+        proptest!(|(mut headers in gen_mandatory_headers(), mobile in mobile)| {
+            let (client_context, identity, encoded) = mobile;
+            let mut context = serde_json::to_string(&client_context).unwrap();
+            if encoded {
+                context = base64::encode(&context);
+            }
+            headers.typed_insert(MobileClientContext(context));
+            headers.typed_insert(MobileClientIdentity(serde_json::to_string(&identity).unwrap()));
+
+            let ctx = LambdaCtx::try_from(headers).unwrap();
+            prop_assert_eq!(ctx.client_context, Some(client_context));
+            prop_assert_eq!(ctx.identity, Some(identity));
+        });
+    }
+
+    #[test]
+    fn ctx_malformed_identity() {
+        proptest!(|(mut headers in gen_mandatory_headers(), identity in gen_client_identity())| {
+            headers.typed_insert(identity);
+
+            let err = LambdaCtx::try_from(headers).unwrap_err();
+            let err = err.downcast_ref::<InvalidHeader>().unwrap();
+            prop_assert_eq!(err.name(), "lambda-runtime-cognito-identity");
+        });
+    }
+
+    #[test]
+    fn cognito_identity_wire_format() {
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(RequestId(String::from("1")));
+        headers.typed_insert(InvocationDeadline(0));
+        headers.typed_insert(FunctionArn(String::from(
+            "arn:aws:lambda:us-east-1:123456789012:function:custom-runtime",
+        )));
+        let identity =
+            r#"{"cognitoIdentityId":"us-east-1:1234","cognitoIdentityPoolId":"us-east-1:5678"}"#;
+        headers.insert("lambda-runtime-cognito-identity", identity.parse().unwrap());
+
+        let identity = LambdaCtx::try_from(headers).unwrap().identity.unwrap();
+        assert_eq!("us-east-1:1234", identity.identity_id);
+        assert_eq!("us-east-1:5678", identity.identity_pool_id);
+    }
 }
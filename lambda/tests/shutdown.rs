@@ -0,0 +1,53 @@
+#![feature(async_await)]
+
+// Runs in its own test binary, as SIGTERM is delivered to every runtime in the process.
+
+use lambda::{
+    emulator::{Emulator, Invocation, Outcome},
+    handler_fn, LambdaCtx, Runtime,
+};
+use signal_hook::{consts::SIGTERM, low_level::raise};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+async fn func(event: String, _ctx: Option<LambdaCtx>) -> Result<String, Err> {
+    if event == "terminate" {
+        raise(SIGTERM)?;
+        // Gives the signal time to reach the runtime before it polls for the next event.
+        runtime::time::Delay::new(Duration::from_millis(100)).await;
+    }
+    Ok(event)
+}
+
+#[runtime::test]
+async fn shutdown_hooks() -> Result<(), Err> {
+    let emulator = Emulator::start()?;
+    let terminate = Invocation::new(r#""terminate""#);
+    let next = Invocation::new(r#""next""#);
+    let (terminate_id, next_id) = (terminate.id().to_string(), next.id().to_string());
+    emulator.push(terminate);
+    emulator.push(next);
+
+    let flushed = Arc::new(AtomicBool::new(false));
+    let hook_flushed = Arc::clone(&flushed);
+    Runtime::new()
+        .on_shutdown(move || {
+            let flushed = Arc::clone(&hook_flushed);
+            async move { flushed.store(true, Ordering::SeqCst) }
+        })
+        .run_with_client(emulator.client(), emulator.config(), handler_fn(func))
+        .await?;
+
+    assert!(flushed.load(Ordering::SeqCst));
+    let response = Outcome::Response(r#""terminate""#.into());
+    assert_eq!(Some(response), emulator.outcome(&terminate_id));
+    assert_eq!(None, emulator.outcome(&next_id));
+    Ok(())
+}